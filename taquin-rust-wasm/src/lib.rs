@@ -9,6 +9,32 @@ mod utils;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+// Random
+
+/// SplitMix64 generator, the same as `taquin_rust::random::SplitMix64`
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Index lower than `len`, picked the same way as `taquin_rust::random::index`
+    fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
 // Taquin
 type Tile = u8;
 
@@ -74,6 +100,7 @@ pub struct Position {
 pub struct Taquin {
     size: u8,
     tiles: Vec<Tile>,
+    goal: Vec<Tile>,
 }
 
 type StateWithHistory = (Taquin, Vec<Move>);
@@ -92,7 +119,19 @@ impl Taquin {
             tiles.push((i + 1) as u8);
         }
         tiles.push(HOLE);
-        Taquin { size, tiles }
+        let goal = tiles.clone();
+        Taquin { size, tiles, goal }
+    }
+
+    /// Start with the solution of another goal, the tiles row by row with `0` for the hole
+    pub fn with_goal(size: u8, goal: Vec<Tile>) -> Self {
+        let taquin = Taquin::new(size);
+        let mut sorted = goal.clone();
+        sorted.sort_unstable();
+        let expected: Vec<Tile> = (0..size * size).collect();
+        if sorted != expected { panic!("Expected the {} tiles of a taquin of size {}, got {:?}", size * size, size, goal); }
+
+        Taquin { tiles: goal.clone(), goal, ..taquin }
     }
 
     pub fn size(&self) -> u8 {
@@ -103,16 +142,12 @@ impl Taquin {
         self.tiles.as_ptr()
     }
 
+    pub fn goal(&self) -> *const Tile {
+        self.goal.as_ptr()
+    }
+
     pub fn is_solved(&self) -> bool {
-        let last_index = (self.size * self.size - 1) as usize;
-        for (i, &tile) in self.tiles.iter().enumerate() {
-            if i == last_index {
-                return tile == HOLE;
-            } else if tile != (i + 1) as u8 {
-                return false;
-            };
-        }
-        false
+        self.tiles == self.goal
     }
 
     pub fn move_hole(&mut self, user_move: Move) -> bool {
@@ -167,37 +202,55 @@ impl Taquin {
         panic!("Hole not found in {:?}", self);
     }
 
-    pub fn shuffle(&mut self, count: u32) {
-        let mut hole_position = self.find_hole();
+    /// Apply `count` random moves with a random seed, the seed is returned to replay the shuffle
+    pub fn shuffle(&mut self, count: u32) -> u64 {
+        let high = (random() * 4_294_967_296.0) as u64;
+        let low = (random() * 4_294_967_296.0) as u64;
+        let seed = high << 32 | low;
+        self.shuffle_with_seed(count, seed);
+        seed
+    }
+
+    /// Apply `count` random moves, the same ones as the native taquin for the same seed
+    pub fn shuffle_with_seed(&mut self, count: u32, seed: u64) {
+        let mut rng = SplitMix64::new(seed);
         let mut last_move = None::<Move>;
 
         for _i in 0..count {
-            // Valid moves
-            let valid_moves = self.valid_moves(hole_position, last_move);
+            // Valid moves, going back from a dead end
+            let hole_position = self.find_hole();
+            let mut valid_moves = self.valid_moves(hole_position, last_move);
+            if valid_moves.is_empty() {
+                valid_moves = self.valid_moves(hole_position, None);
+            }
 
             // Shuffle
-            let index = (random() * valid_moves.len() as f64).floor() as usize;
-            let current_move = valid_moves[index];
+            let current_move = valid_moves[rng.index(valid_moves.len())];
 
             // update
-            hole_position = self.find_hole();
             last_move = Some(current_move);
             self.move_hole(current_move);
         }
     }
 
     pub fn solve(&self) -> Moves {
+        self.solve_with_limit(usize::MAX)
+            .expect("Should be solvable")
+    }
+
+    /// Give up when more than `max_states` states are visited
+    pub fn solve_with_limit(&self, max_states: usize) -> Option<Moves> {
         let mut states = HashSet::new();
         states.insert(self.clone());
         let initial = vec![(self.clone(), vec![])];
-        let moves = Taquin::solve_aux(initial, &mut states);
-        Moves(moves)
+        Taquin::solve_aux(initial, &mut states, max_states)
+            .map(Moves)
     }
 
     fn valid_moves(&self, hole_position: Position, last_move: Option<Move>) -> Vec<Move> {
         let mut valid_moves = vec![];
         for m in Move::all() {
-            let not_back = last_move.map_or(true, |last| last != m.reverse());
+            let not_back = last_move != Some(m.reverse());
             if not_back && self.is_valid(m, hole_position) {
                 valid_moves.push(m);
             }
@@ -206,13 +259,18 @@ impl Taquin {
     }
 
     // Solving aux
-    fn solve_aux(states_with_history: Vec<StateWithHistory>, visited_states: &mut HashSet<Taquin>) -> Vec<Move> {
+    fn solve_aux(states_with_history: Vec<StateWithHistory>, visited_states: &mut HashSet<Taquin>,
+                 max_states: usize) -> Option<Vec<Move>> {
+        // Nowhere to go, or too far
+        if states_with_history.is_empty() || visited_states.len() > max_states {
+            return None;
+        }
+
         let mut next = vec![];
-        println!("Visited: {}", visited_states.len());
         for (taquin, history) in states_with_history {
             // Try found solution
             if taquin.is_solved() {
-                return history;
+                return Some(history);
             }
 
             // Find next states
@@ -235,7 +293,7 @@ impl Taquin {
         }
 
         // Deeper
-        Taquin::solve_aux(next, visited_states)
+        Taquin::solve_aux(next, visited_states, max_states)
     }
 }
 
@@ -258,6 +316,15 @@ mod test {
         assert_eq!(Position { row: 2, column: 2 }, taquin.get_position(8));
     }
 
+    #[test]
+    fn shuffle_with_seed() {
+        // same tiles as `taquin_rust::taquin::Taquin::shuffle_with_seed`
+        let mut taquin = Taquin::new(3);
+        taquin.shuffle_with_seed(30, 2021);
+
+        assert_eq!(taquin.tiles, vec![6, 4, 1, 5, 0, 3, 7, 2, 8]);
+    }
+
     #[test]
     fn get_index() {
         let taquin = Taquin::new(3);
@@ -282,6 +349,7 @@ mod test {
                 8, 1, 2,
                 4, 7, 6,
             ],
+            ..Taquin::new(3)
         };
 
         assert_eq!(Position { row: 0, column: 1 }, taquin.find_hole());
@@ -299,10 +367,27 @@ mod test {
                 8, 1, 2,
                 4, 7, 6,
             ],
+            ..Taquin::new(3)
         };
         assert!(!taquin.is_solved());
     }
 
+    #[test]
+    fn with_goal() {
+        let mut taquin = Taquin::with_goal(3, vec![
+            1, 2, 3,
+            8, 0, 4,
+            7, 6, 5,
+        ]);
+        assert!(taquin.is_solved());
+
+        taquin.move_hole(Move::Up);
+        assert!(!taquin.is_solved());
+
+        let moves = taquin.solve();
+        assert_eq!(1, moves.size());
+    }
+
     #[test]
     fn is_valid() {
         let taquin = Taquin::new(3);
@@ -347,6 +432,7 @@ mod test {
                 8, 1, 2,
                 4, 7, 6,
             ],
+            ..Taquin::new(3)
         };
 
         taquin.move_hole(Move::Up);
@@ -387,6 +473,7 @@ mod test {
                 8, 1, 2,
                 4, 7, 6,
             ],
+            ..Taquin::new(3)
         };
 
         let result = taquin.solve();
@@ -394,6 +481,37 @@ mod test {
         assert_eq!(31, result.size());
     }
 
+    #[test]
+    fn solve_with_limit() {
+        let taquin = Taquin {
+            size: 3,
+            tiles: vec![
+                5, 0, 3,
+                8, 1, 2,
+                4, 7, 6,
+            ],
+            ..Taquin::new(3)
+        };
+
+        assert!(taquin.solve_with_limit(100).is_none());
+        assert_eq!(31, taquin.solve_with_limit(1_000_000).unwrap().size());
+    }
+
+    #[test]
+    fn solve_with_limit_unsolvable() {
+        let taquin = Taquin {
+            size: 3,
+            tiles: vec![
+                2, 1, 3,
+                4, 5, 6,
+                7, 8, 0,
+            ],
+            ..Taquin::new(3)
+        };
+
+        assert!(taquin.solve_with_limit(usize::MAX).is_none());
+    }
+
     // #[test]
     // fn solve_4x4() {
     //     let taquin = Taquin {
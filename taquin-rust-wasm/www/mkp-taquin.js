@@ -20,6 +20,9 @@ moveClassName[Move.Right] = 'right';
 moveClassName[Move.Down] = 'down';
 moveClassName[Move.Left] = 'left';
 
+// Give up cheating instead of freezing the page
+const maxCheatStates = 1000000;
+
 const delay = (delayInMs, block) => new Promise(resolve => {
     setTimeout(function () {
         resolve(block && block());
@@ -259,7 +262,11 @@ export class MkpTaquinElt extends HTMLElement {
 
     cheat() {
         console.log('Cheat');
-        const solution = this.taquin.solve();
+        const solution = this.taquin.solve_with_limit(maxCheatStates);
+        if (!solution) {
+            console.log('Give up after', maxCheatStates, 'states');
+            return;
+        }
         let count = solution.size();
         console.log('Solved in', count, 'moves');
         const moves = new Uint8Array(memory.buffer, solution.moves(), count);
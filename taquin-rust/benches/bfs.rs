@@ -0,0 +1,73 @@
+//! Compare the iterative BFS with the former recursive one on `taquin_3x3.txt`,
+//! and with the packed taquin representation
+//!
+//! Run with `cargo bench --bench bfs`
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::BufReader;
+use std::time::{Duration, Instant};
+
+use taquin_rust::input::read_taquins;
+use taquin_rust::packed::PackedTaquin;
+use taquin_rust::solver::Problem;
+use taquin_rust::taquin::{Move, Taquin};
+
+/// Former recursive BFS, each state carries its whole history
+fn solve_recursive(taquin: &Taquin) -> Vec<Move> {
+    let mut states = HashSet::new();
+    states.insert(taquin.clone());
+    let initial = vec![(taquin.clone(), vec![])];
+
+    solve_aux(initial, &mut states)
+}
+
+fn solve_aux(history: Vec<(Taquin, Vec<Move>)>, visited: &mut HashSet<Taquin>) -> Vec<Move> {
+    let mut next = vec![];
+    for (state, steps) in history {
+        if state.is_solved() {
+            return steps;
+        }
+
+        for step in state.available_steps(&steps) {
+            let new_state = state.next(step);
+            if !visited.contains(&new_state) {
+                visited.insert(new_state.clone());
+                let mut next_history = steps.clone();
+                next_history.push(step);
+                next.push((new_state, next_history));
+            }
+        }
+    }
+    solve_aux(next, visited)
+}
+
+fn measure<F>(name: &str, taquins: &[Taquin], solve: F) -> Duration
+    where F: Fn(&Taquin) -> usize {
+    let start = Instant::now();
+    let moves: usize = taquins.iter().map(solve).sum();
+    let elapsed = start.elapsed();
+    println!("{:>10}: {} taquins, {} moves in {:?}", name, taquins.len(), moves, elapsed);
+    elapsed
+}
+
+fn main() {
+    let file = File::open("taquin_3x3.txt").expect("Cannot open taquin_3x3.txt");
+    let taquins: Vec<Taquin> = read_taquins(BufReader::new(file))
+        .expect("Cannot read taquin_3x3.txt")
+        .into_iter()
+        .filter_map(|(_line, taquin)| taquin.ok())
+        .filter(Taquin::is_solvable)
+        .collect();
+
+    let recursive = measure("recursive", &taquins, |taquin| solve_recursive(taquin).len());
+    let iterative = measure("iterative", &taquins, |taquin| taquin.solve().expect("Should be solvable").len());
+    let packed = measure("packed", &taquins, |taquin| {
+        let packed = PackedTaquin::try_from(taquin).expect("Should be a 3x3");
+        packed.solve().expect("Should be solvable").len()
+    });
+    println!("Speedup: {:.2}x iterative, {:.2}x packed",
+             recursive.as_secs_f64() / iterative.as_secs_f64(),
+             recursive.as_secs_f64() / packed.as_secs_f64());
+}
@@ -0,0 +1,488 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use crate::goal::Goal;
+use crate::grid::Dimensions;
+use crate::heuristic::ManhattanDistance;
+use crate::solver::{Monitor, Problem, SolverError};
+use crate::taquin::{Move, Taquin, TileValue};
+
+/// Number of moves replaced at once by an optimal sequence when shortening a path
+const SHORTEN_WINDOW: usize = 12;
+
+/// Tiles of a taquin being solved, with the moves played so far
+///
+/// Some cells are fixed: the hole and the moved tiles go around them.
+struct Board {
+    columns: usize,
+    rows: usize,
+    values: Vec<TileValue>,
+    hole: usize,
+    fixed: Vec<bool>,
+    moves: Vec<Move>,
+}
+
+impl Board {
+    fn new(dimensions: Dimensions, values: Vec<TileValue>) -> Self {
+        let hole = values.iter().position(|&value| value == 0)
+            .expect("No Hole found !");
+        Board {
+            columns: usize::from(dimensions.columns()),
+            rows: usize::from(dimensions.rows()),
+            fixed: vec![false; values.len()],
+            values,
+            hole,
+            moves: vec![],
+        }
+    }
+
+    fn cell(&self, row: usize, column: usize) -> usize {
+        row * self.columns + column
+    }
+
+    fn position(&self, value: TileValue) -> usize {
+        self.values.iter().position(|&other| other == value)
+            .expect("Tile should be on the board")
+    }
+
+    fn neighbors(&self, cell: usize) -> Vec<usize> {
+        let (row, column) = (cell / self.columns, cell % self.columns);
+        let mut result = vec![];
+        if row > 0 { result.push(cell - self.columns); }
+        if row + 1 < self.rows { result.push(cell + self.columns); }
+        if column > 0 { result.push(cell - 1); }
+        if column + 1 < self.columns { result.push(cell + 1); }
+        result
+    }
+
+    /// Shortest path between two cells through the cells that are not fixed, the start excluded
+    fn path(&self, from: usize, to: usize, avoid: Option<usize>) -> Option<Vec<usize>> {
+        let mut previous = vec![None; self.values.len()];
+        let mut queue = VecDeque::new();
+        previous[from] = Some(from);
+        queue.push_back(from);
+        while let Some(cell) = queue.pop_front() {
+            if cell == to { break; }
+            for neighbor in self.neighbors(cell) {
+                if previous[neighbor].is_none() && !self.fixed[neighbor] && Some(neighbor) != avoid {
+                    previous[neighbor] = Some(cell);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        previous[to]?;
+        let mut path = vec![];
+        let mut cell = to;
+        while cell != from {
+            path.push(cell);
+            cell = previous[cell].expect("Visited cell");
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Move the hole to a neighbor cell
+    fn step(&mut self, cell: usize) {
+        let user_move = if cell == self.hole + self.columns {
+            Move::Up
+        } else if cell + self.columns == self.hole {
+            Move::Down
+        } else if cell + 1 == self.hole {
+            Move::Right
+        } else {
+            Move::Left
+        };
+        self.values.swap(self.hole, cell);
+        self.hole = cell;
+        self.moves.push(user_move);
+    }
+
+    fn move_hole(&mut self, to: usize, avoid: Option<usize>) -> Result<(), SolverError> {
+        let path = self.path(self.hole, to, avoid).ok_or(SolverError::NoSolutionFound)?;
+        path.into_iter().for_each(|cell| self.step(cell));
+        Ok(())
+    }
+
+    /// Bring a tile to a cell, one cell at a time, the hole goes around the tile
+    fn move_tile(&mut self, value: TileValue, to: usize) -> Result<(), SolverError> {
+        let mut current = self.position(value);
+        while current != to {
+            let next = self.path(current, to, None).ok_or(SolverError::NoSolutionFound)?[0];
+            self.move_hole(next, Some(current))?;
+            self.step(current);
+            current = next;
+        }
+        Ok(())
+    }
+
+    /// Solve a row or a column, the cells are given from the outside corner
+    ///
+    /// The last two tiles cannot be placed one after the other: the last tile goes to
+    /// the second to last cell, the second to last tile goes next to it inside the board,
+    /// then the hole comes to the last cell and rotates them into place.
+    /// The last cell is a dead end meanwhile: when the second to last tile is stuck there,
+    /// both tiles are placed by a search in the two lines next to them.
+    ///
+    /// Each placed tile counts as an expanded state for the monitor.
+    fn solve_line<M>(&mut self, cells: &[usize], inside: usize, targets: &[TileValue], monitor: &mut M) -> Result<(), SolverError>
+        where M: Monitor<Move> {
+        let count = cells.len();
+        for (&cell, &value) in cells[..count - 2].iter().zip(targets) {
+            monitor.check()?;
+            self.move_tile(value, cell)?;
+            self.fixed[cell] = true;
+            monitor.expanded(1, 0);
+        }
+
+        monitor.check()?;
+        let (before_last, last) = (cells[count - 2], cells[count - 1]);
+        let (first_value, last_value) = (targets[count - 2], targets[count - 1]);
+        if self.values[before_last] != first_value || self.values[last] != last_value {
+            self.move_tile(last_value, before_last)?;
+            self.fixed[before_last] = true;
+            let inward = inside - before_last;
+            if self.hole == last {
+                self.step(last + inward);
+            }
+
+            if self.values[last] == first_value {
+                self.move_hole(last + inward, Some(last))?;
+                self.fixed[before_last] = false;
+                let window: Vec<usize> = (0..3)
+                    .flat_map(|line| vec![before_last + line * inward, last + line * inward])
+                    .collect();
+                self.place_pair(&window, (first_value, before_last), (last_value, last))?;
+            } else {
+                self.move_tile(first_value, inside)?;
+                self.fixed[inside] = true;
+                self.move_hole(last, None)?;
+                self.fixed[before_last] = false;
+                self.fixed[inside] = false;
+                self.step(before_last);
+                self.step(inside);
+            }
+        }
+        self.fixed[before_last] = true;
+        self.fixed[last] = true;
+        monitor.expanded(2, 0);
+        Ok(())
+    }
+
+    /// BFS on the positions of two tiles and the hole, all of them staying in the window
+    fn place_pair(&mut self, window: &[usize], first: (TileValue, usize), second: (TileValue, usize)) -> Result<(), SolverError> {
+        let start = (self.position(first.0), self.position(second.0), self.hole);
+        let mut previous = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut found = None;
+        previous.insert(start, start);
+        queue.push_back(start);
+        while let Some(state) = queue.pop_front() {
+            let (first_cell, second_cell, hole) = state;
+            if first_cell == first.1 && second_cell == second.1 {
+                found = Some(state);
+                break;
+            }
+            for cell in self.neighbors(hole).into_iter().filter(|cell| window.contains(cell)) {
+                let moved = |tile: usize| if tile == cell { hole } else { tile };
+                let next = (moved(first_cell), moved(second_cell), cell);
+                if let Entry::Vacant(entry) = previous.entry(next) {
+                    entry.insert(state);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut state = found.ok_or(SolverError::NoSolutionFound)?;
+        let mut holes = vec![];
+        while state != start {
+            holes.push(state.2);
+            state = previous[&state];
+        }
+        holes.into_iter().rev().for_each(|cell| self.step(cell));
+        Ok(())
+    }
+
+    /// Optimal moves for the remaining region, the other tiles are already in place
+    fn solve_region<M>(&mut self, top: usize, left: usize, goal: &[TileValue], monitor: &mut M) -> Result<(), SolverError>
+        where M: Monitor<Move> {
+        let cells: Vec<usize> = (top..self.rows)
+            .flat_map(|row| (left..self.columns).map(move |column| (row, column)))
+            .map(|(row, column)| self.cell(row, column))
+            .collect();
+        // Relabel the tiles of the region from their goal cell
+        let mut labels = HashMap::new();
+        for (index, &cell) in cells.iter().filter(|&&cell| goal[cell] != 0).enumerate() {
+            labels.insert(goal[cell], index as TileValue + 1);
+        }
+        let label = |value: TileValue| if value == 0 { 0 } else { labels[&value] };
+
+        let dimensions = Dimensions::new((self.rows - top) as u8, (self.columns - left) as u8);
+        let region_goal: Vec<TileValue> = cells.iter().map(|&cell| label(goal[cell])).collect();
+        let region: Vec<TileValue> = cells.iter().map(|&cell| label(self.values[cell])).collect();
+        let goal = Goal::custom(dimensions, &region_goal).expect("Region goal should be valid");
+        let taquin = Taquin::from_values(dimensions, &region).with_goal(goal);
+
+        // The moves are the same on the whole board, the hole stays in the region
+        for user_move in taquin.solve_with_monitor(monitor)? {
+            let cell = self.neighbor(user_move);
+            self.step(cell);
+        }
+        Ok(())
+    }
+
+    fn neighbor(&self, user_move: Move) -> usize {
+        match user_move {
+            Move::Up => self.hole + self.columns,
+            Move::Right => self.hole - 1,
+            Move::Down => self.hole - self.columns,
+            Move::Left => self.hole + 1,
+        }
+    }
+}
+
+impl Taquin {
+    /// Solve without searching, as a human would do
+    ///
+    /// The top row or the left column of the remaining region is solved, so the region
+    /// shrinks until it's at most 2x3 or 3x2, that last region is solved with a BFS.
+    /// The number of moves grows polynomially with the size, but the solution is far from optimal,
+    /// see `Taquin::shorten`.
+    pub fn solve_constructive(&self) -> Result<Vec<Move>, SolverError> {
+        self.solve_constructive_with_monitor(&mut ())
+    }
+
+    /// The monitor is checked before placing each tile, a placed tile counts as an expanded state
+    pub fn solve_constructive_with_monitor<M>(&self, monitor: &mut M) -> Result<Vec<Move>, SolverError>
+        where M: Monitor<Move> {
+        if !self.is_solvable() { return Err(SolverError::Unsolvable); }
+
+        // Solve towards the goal with the hole in the bottom right corner, then bring the hole back
+        let dimensions = self.dimensions();
+        let mut goal = Board::new(dimensions, self.target().tiles().to_vec());
+        goal.move_hole(goal.values.len() - 1, None)?;
+        let back: Vec<Move> = goal.moves.iter().rev().map(|m| m.reverse()).collect();
+        let goal = goal.values;
+
+        let mut board = Board::new(dimensions, self.tile_values());
+        let (mut top, mut left) = (0, 0);
+        loop {
+            let (rows, columns) = (board.rows - top, board.columns - left);
+            if rows * columns <= 6 { break; }
+
+            if rows > 2 && (rows >= columns || columns == 2) {
+                let cells: Vec<usize> = (left..board.columns).map(|column| board.cell(top, column)).collect();
+                let inside = board.cell(top + 1, board.columns - 2);
+                let targets: Vec<TileValue> = cells.iter().map(|&cell| goal[cell]).collect();
+                board.solve_line(&cells, inside, &targets, monitor)?;
+                top += 1;
+            } else {
+                let cells: Vec<usize> = (top..board.rows).map(|row| board.cell(row, left)).collect();
+                let inside = board.cell(board.rows - 2, left + 1);
+                let targets: Vec<TileValue> = cells.iter().map(|&cell| goal[cell]).collect();
+                board.solve_line(&cells, inside, &targets, monitor)?;
+                left += 1;
+            }
+        }
+        board.solve_region(top, left, &goal, monitor)?;
+
+        let mut moves = board.moves;
+        moves.extend(back);
+        monitor.found(&moves);
+        Ok(moves)
+    }
+
+    /// Shorter moves reaching the same taquin
+    ///
+    /// The loops are removed, going back to an already seen taquin, then each window of
+    /// a few moves is replaced by an optimal sequence between its first and last taquins.
+    pub fn shorten(&self, moves: &[Move]) -> Vec<Move> {
+        self.shorten_with_monitor(moves, &mut ())
+    }
+
+    /// The windows are searched with the monitor, once it stops the search the remaining windows are kept
+    pub fn shorten_with_monitor<M>(&self, moves: &[Move], monitor: &mut M) -> Vec<Move>
+        where M: Monitor<Move> {
+        let mut moves = remove_loops(self, moves);
+        for offset in [0, SHORTEN_WINDOW / 2] {
+            moves = optimize_windows(self, &moves, offset, monitor);
+        }
+        moves
+    }
+}
+
+/// Jump from each taquin to its last occurrence on the path
+fn remove_loops(taquin: &Taquin, moves: &[Move]) -> Vec<Move> {
+    let mut states = vec![taquin.tile_values()];
+    let mut current = taquin.clone();
+    for &user_move in moves {
+        current = current.move_hole(user_move);
+        states.push(current.tile_values());
+    }
+    let last: HashMap<&[TileValue], usize> = states.iter()
+        .enumerate()
+        .map(|(index, state)| (state.as_slice(), index))
+        .collect();
+
+    let mut result = vec![];
+    let mut index = 0;
+    while index < moves.len() {
+        index = last[states[index].as_slice()];
+        if index < moves.len() {
+            result.push(moves[index]);
+            index += 1;
+        }
+    }
+    result
+}
+
+/// Replace each window after the offset by optimal moves between its first and last taquins
+fn optimize_windows<M>(taquin: &Taquin, moves: &[Move], offset: usize, monitor: &mut M) -> Vec<Move>
+    where M: Monitor<Move> {
+    let offset = offset.min(moves.len());
+    let mut result = moves[..offset].to_vec();
+    let mut current = result.iter().fold(taquin.clone(), |t, m| t.move_hole(*m));
+    for window in moves[offset..].chunks(SHORTEN_WINDOW) {
+        let end = window.iter().fold(current.clone(), |t, m| t.move_hole(*m));
+        // Only a limit of the monitor stops the search, the end of the window is reachable
+        match current.with_goal(Goal::from(&end)).solve_idastar_with_monitor(&ManhattanDistance, monitor) {
+            Ok(optimal) if optimal.len() < window.len() => result.extend(optimal),
+            _ => result.extend_from_slice(window),
+        }
+        current = end;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{Limits, Stats};
+
+    mod given {
+        use super::*;
+
+        pub fn a_taquin() -> Taquin {
+            Taquin::from_str(3, "6,4,7,  8,5,0,  3,2,1")
+        }
+
+        /// Tiles in reverse order, two tiles swapped when needed to be solvable
+        pub fn a_large_taquin(dimensions: Dimensions) -> Taquin {
+            let mut values: Vec<TileValue> = (0..dimensions.cell_count() as TileValue).rev().collect();
+            let taquin = Taquin::from_values(dimensions, &values);
+            if !taquin.is_solvable() {
+                values.swap(0, 1);
+            }
+            Taquin::from_values(dimensions, &values)
+        }
+
+        pub fn end(taquin: &Taquin, moves: &[Move]) -> Taquin {
+            moves.iter().fold(taquin.clone(), |t, m| t.try_move_hole(*m).expect("Valid move"))
+        }
+    }
+
+    mod solve_constructive {
+        use super::*;
+
+        #[test]
+        fn ok() {
+            let taquin = given::a_taquin();
+            let moves = taquin.solve_constructive().unwrap();
+            assert!(given::end(&taquin, &moves).is_solved());
+        }
+
+        #[test]
+        fn already_solved() {
+            assert_eq!(Taquin::new(3).solve_constructive(), Ok(vec![]));
+            assert_eq!(Taquin::new(5).solve_constructive(), Ok(vec![]));
+        }
+
+        #[test]
+        fn large() {
+            for &dimensions in [Dimensions::from(10), Dimensions::from(20), Dimensions::new(4, 9), Dimensions::new(9, 2)].iter() {
+                let taquin = given::a_large_taquin(dimensions);
+                let moves = taquin.solve_constructive().unwrap();
+                assert!(given::end(&taquin, &moves).is_solved(), "{}", dimensions);
+            }
+        }
+
+        #[test]
+        fn with_goal() {
+            for goal in [Goal::hole_first(4), Goal::spiral(4), Goal::spiral(Dimensions::new(3, 5))] {
+                let taquin = given::a_large_taquin(goal.dimensions()).with_goal(goal.clone());
+                let taquin = if taquin.is_solvable() {
+                    taquin
+                } else {
+                    let mut values = taquin.tile_values();
+                    values.swap(0, 1);
+                    Taquin::from_values(goal.dimensions(), &values).with_goal(goal)
+                };
+                let moves = taquin.solve_constructive().unwrap();
+                assert!(given::end(&taquin, &moves).is_solved());
+            }
+        }
+
+        #[test]
+        fn unsolvable() {
+            let taquin = Taquin::from_str(3, "2,1,3,  4,5,6,  7,8,0");
+            assert_eq!(taquin.solve_constructive(), Err(SolverError::Unsolvable));
+        }
+    }
+
+    mod solve_constructive_with_monitor {
+        use super::*;
+
+        #[test]
+        fn stats() {
+            let taquin = given::a_large_taquin(Dimensions::from(6));
+            let mut stats = Stats::default();
+            let moves = taquin.solve_constructive_with_monitor(&mut stats).unwrap();
+            assert!(given::end(&taquin, &moves).is_solved());
+            assert!(stats.expanded > 0);
+        }
+
+        #[test]
+        fn limit_reached() {
+            let taquin = given::a_large_taquin(Dimensions::from(10));
+            let result = taquin.solve_constructive_with_monitor(&mut Limits::new().max_expanded(3));
+            assert_eq!(result, Err(SolverError::LimitReached { expanded: 3, depth: 0 }));
+        }
+    }
+
+    mod shorten {
+        use super::*;
+
+        #[test]
+        fn loops() {
+            let taquin = Taquin::new(3);
+            let moves = [Move::Down, Move::Right, Move::Left, Move::Up, Move::Right, Move::Left];
+            assert_eq!(taquin.shorten(&moves), vec![]);
+        }
+
+        #[test]
+        fn windows() {
+            // going around a 2x2 square the other way is shorter
+            let taquin = Taquin::new(3);
+            let moves = [Move::Down, Move::Right, Move::Up, Move::Left, Move::Down, Move::Right, Move::Up];
+            assert_eq!(given::end(&taquin, &taquin.shorten(&moves)), given::end(&taquin, &moves));
+            assert!(taquin.shorten(&moves).len() < moves.len());
+        }
+
+        #[test]
+        fn constructive() {
+            let taquin = given::a_large_taquin(Dimensions::from(6));
+            let moves = taquin.solve_constructive().unwrap();
+            let shorter = taquin.shorten(&moves);
+            assert!(shorter.len() < moves.len());
+            assert!(given::end(&taquin, &shorter).is_solved());
+        }
+
+        #[test]
+        fn limit_reached() {
+            let taquin = given::a_large_taquin(Dimensions::from(6));
+            let moves = taquin.solve_constructive().unwrap();
+            let mut limits = Limits::new().max_expanded(0);
+            assert_eq!(taquin.shorten_with_monitor(&moves, &mut limits), remove_loops(&taquin, &moves));
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+use crate::goal::Goal;
+use crate::grid::Dimensions;
+use crate::packed::PackedTaquin;
+use crate::permutation;
+use crate::solver::{Heuristic, Problem, SolverError};
+use crate::taquin::{Move, Taquin, TileValue};
+
+/// Number of solvable 3x3 taquins, 9! / 2
+pub const STATE_COUNT: usize = 181_440;
+
+/// Placements of the 8 tiles for one position of the hole, half of them are solvable
+const TILE_PLACEMENTS: usize = 20_160;
+const UNKNOWN: u8 = u8::MAX;
+
+/// Optimal distance and best next move of every solvable 3x3 taquin
+///
+/// A BFS from the goal visits the whole state space, each entry is indexed by the position
+/// of the hole and the rank of the tiles. Swapping the last two tiles changes the solvability,
+/// so the rank is halved to only index the solvable taquins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceDatabase {
+    goal: Goal,
+    /// Distance in the high bits, next move in the two low bits
+    entries: Vec<u8>,
+}
+
+impl DistanceDatabase {
+    /// Database of the sorted goal
+    pub fn new() -> Self {
+        DistanceDatabase::build_for_goal(Goal::sorted(3))
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the goal is not a 3x3 taquin.
+    pub fn build_for_goal(goal: Goal) -> Self {
+        if goal.dimensions() != Dimensions::from(3) {
+            panic!("Goal size should be 3, got {}", goal.dimensions());
+        }
+        let solved = PackedTaquin::try_from(&Taquin::solved(goal.clone()))
+            .expect("A 3x3 taquin should be packed");
+
+        let mut entries = vec![UNKNOWN; STATE_COUNT];
+        entries[index(&solved.tile_values())] = 0;
+        let mut frontier = VecDeque::new();
+        frontier.push_back((solved, 0));
+        while let Some((state, distance)) = frontier.pop_front() {
+            for step in state.available_steps(&[]) {
+                let next = state.next(step);
+                let next_index = index(&next.tile_values());
+                if entries[next_index] == UNKNOWN {
+                    entries[next_index] = (distance + 1) << 2 | step.reverse() as u8;
+                    frontier.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        DistanceDatabase { goal, entries }
+    }
+
+    pub fn goal(&self) -> &Goal {
+        &self.goal
+    }
+
+    /// Fewest moves to solve the taquin, `None` if it is not solvable
+    pub fn distance(&self, taquin: &Taquin) -> Option<u32> {
+        self.entry(taquin).map(|entry| u32::from(entry >> 2))
+    }
+
+    /// First move of an optimal solution, `None` if the taquin is solved or not solvable
+    pub fn next_move(&self, taquin: &Taquin) -> Option<Move> {
+        self.entry(taquin)
+            .filter(|&entry| entry >> 2 > 0)
+            .map(|entry| Move::all()[usize::from(entry & 3)])
+    }
+
+    /// Optimal solution, following the next moves
+    pub fn solve(&self, taquin: &Taquin) -> Result<Vec<Move>, SolverError> {
+        if self.entry(taquin).is_none() { return Err(SolverError::Unsolvable); }
+
+        let mut steps = vec![];
+        let mut current = taquin.clone();
+        while let Some(step) = self.next_move(&current) {
+            current = current.move_hole(step);
+            steps.push(step);
+        }
+        Ok(steps)
+    }
+
+    /// Number of taquins at each distance, from the goal to the farthest taquins
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![];
+        for &entry in &self.entries {
+            let distance = usize::from(entry >> 2);
+            if counts.len() <= distance {
+                counts.resize(distance + 1, 0);
+            }
+            counts[distance] += 1;
+        }
+        counts
+    }
+
+    /// Number of taquins whose optimal solution has exactly `distance` moves
+    pub fn count(&self, distance: u32) -> usize {
+        self.counts().get(distance as usize).copied().unwrap_or(0)
+    }
+
+    /// Moves of the hardest taquins
+    pub fn max_distance(&self) -> u32 {
+        self.entries.iter()
+            .map(|&entry| u32::from(entry >> 2))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn entry(&self, taquin: &Taquin) -> Option<u8> {
+        if taquin.dimensions() != self.goal.dimensions() || !taquin.is_solvable() {
+            return None;
+        }
+        debug_assert_eq!(taquin.target(), &self.goal);
+        Some(self.entries[index(&taquin.tile_values())])
+    }
+}
+
+impl Default for DistanceDatabase {
+    fn default() -> Self {
+        DistanceDatabase::new()
+    }
+}
+
+/// Exact distance, the taquin should have the goal of the database
+impl Heuristic<Taquin> for DistanceDatabase {
+    fn estimate(&self, taquin: &Taquin) -> u32 {
+        debug_assert_eq!(taquin.target(), &self.goal);
+        u32::from(self.entries[index(&taquin.tile_values())] >> 2)
+    }
+}
+
+/// Exact distance, the taquin should have the goal of the database
+impl Heuristic<PackedTaquin> for DistanceDatabase {
+    fn estimate(&self, taquin: &PackedTaquin) -> u32 {
+        debug_assert_eq!(taquin.target(), self.goal);
+        u32::from(self.entries[index(&taquin.tile_values())] >> 2)
+    }
+}
+
+/// Position of the hole, then the halved rank of the tiles
+fn index(values: &[TileValue]) -> usize {
+    let hole = values.iter().position(|&value| value == 0).expect("No Hole found !");
+    let tiles: Vec<TileValue> = values.iter()
+        .filter(|&&value| value != 0)
+        .map(|&value| value - 1)
+        .collect();
+    hole * TILE_PLACEMENTS + permutation::rank(tiles) as usize / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod given {
+        use super::*;
+
+        pub fn a_taquin() -> Taquin {
+            Taquin::from_str(3, "8,6,7,  2,5,4,  3,0,1")
+        }
+    }
+
+    mod index {
+        use super::*;
+
+        #[test]
+        fn first() {
+            assert_eq!(index(&[0, 1, 2, 3, 4, 5, 6, 7, 8]), 0);
+            assert_eq!(index(&[0, 1, 2, 3, 4, 5, 6, 8, 7]), 0);
+        }
+
+        #[test]
+        fn last() {
+            assert_eq!(index(&[8, 7, 6, 5, 4, 3, 2, 1, 0]), STATE_COUNT - 1);
+        }
+    }
+
+    mod distance_database {
+        use super::*;
+
+        #[test]
+        fn all_states() {
+            let database = DistanceDatabase::new();
+            assert!(!database.entries.contains(&UNKNOWN));
+            assert_eq!(database.counts().iter().sum::<usize>(), STATE_COUNT);
+        }
+
+        #[test]
+        fn statistics() {
+            let database = DistanceDatabase::new();
+            assert_eq!(database.max_distance(), 31);
+            assert_eq!(&database.counts()[..5], &[1, 2, 4, 8, 16]);
+            assert_eq!(database.count(31), 2);
+            assert_eq!(database.count(32), 0);
+        }
+
+        #[test]
+        fn lookups() {
+            let database = DistanceDatabase::new();
+            let taquin = given::a_taquin();
+            assert_eq!(database.distance(&taquin), Some(31));
+            assert_eq!(database.distance(&Taquin::new(3)), Some(0));
+            assert_eq!(database.next_move(&Taquin::new(3)), None);
+
+            let unsolvable = Taquin::from_str(3, "1,2,3,  4,5,6,  8,7,0");
+            assert_eq!(database.distance(&unsolvable), None);
+            assert_eq!(database.solve(&unsolvable), Err(SolverError::Unsolvable));
+
+            let steps = database.solve(&taquin).expect("Should be solvable");
+            assert_eq!(steps.len(), 31);
+            let end = steps.iter().fold(taquin, |t, m| t.move_hole(*m));
+            assert!(end.is_solved());
+        }
+
+        #[test]
+        fn other_goal() {
+            let database = DistanceDatabase::build_for_goal(Goal::hole_first(3));
+            assert_eq!(database.distance(&Taquin::solved(Goal::hole_first(3))), Some(0));
+            assert_eq!(database.counts().iter().sum::<usize>(), STATE_COUNT);
+        }
+
+        #[test]
+        #[should_panic(expected = "Goal size should be 3, got 4")]
+        fn invalid_size() {
+            DistanceDatabase::build_for_goal(Goal::sorted(4));
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::grid::Dimensions;
+use crate::taquin::{ParseError, Taquin, TileValue};
+
+/// Target layout of a taquin
+///
+/// The tiles are listed row by row with `0` for the hole, the positions
+/// give the target index of each value.
+#[derive(Hash, Clone, Debug, PartialEq, Eq)]
+pub struct Goal {
+    dimensions: Dimensions,
+    tiles: Vec<TileValue>,
+    positions: Vec<TileValue>,
+}
+
+impl Goal {
+    /// `1` to `n-1` row by row, then the hole
+    pub fn sorted<D: Into<Dimensions>>(dimensions: D) -> Self {
+        let dimensions = dimensions.into();
+        let count = dimensions.cell_count();
+        let mut tiles: Vec<TileValue> = (1..count).map(|value| value as TileValue).collect();
+        tiles.push(0);
+        Goal::from_tiles(dimensions, tiles)
+    }
+
+    /// The hole, then `1` to `n-1` row by row
+    pub fn hole_first<D: Into<Dimensions>>(dimensions: D) -> Self {
+        let dimensions = dimensions.into();
+        let tiles = (0..dimensions.cell_count()).map(|value| value as TileValue).collect();
+        Goal::from_tiles(dimensions, tiles)
+    }
+
+    /// `1` to `n-1` clockwise from the top left corner, the hole ends the spiral
+    pub fn spiral<D: Into<Dimensions>>(dimensions: D) -> Self {
+        let dimensions = dimensions.into();
+        let columns = usize::from(dimensions.columns());
+        let count = dimensions.cell_count();
+        let mut tiles = vec![0; count];
+        let (mut top, mut left) = (0, 0);
+        let (mut bottom, mut right) = (usize::from(dimensions.rows()) - 1, columns - 1);
+        let mut value = 1;
+        let mut set = |row: usize, column: usize| {
+            if value < count {
+                tiles[row * columns + column] = value as TileValue;
+                value += 1;
+            }
+        };
+        while top <= bottom && left <= right {
+            (left..=right).for_each(|column| set(top, column));
+            (top + 1..=bottom).for_each(|row| set(row, right));
+            if top < bottom && left < right {
+                (left..right).rev().for_each(|column| set(bottom, column));
+                (top + 1..bottom).rev().for_each(|row| set(row, left));
+            }
+            top += 1;
+            left += 1;
+            if bottom == 0 || right == 0 { break; }
+            bottom -= 1;
+            right -= 1;
+        }
+        Goal::from_tiles(dimensions, tiles)
+    }
+
+    /// Any layout of all the tiles, `0` stands for the hole
+    pub fn custom<D: Into<Dimensions>>(dimensions: D, tiles: &[TileValue]) -> Result<Self, ParseError> {
+        let dimensions = dimensions.into();
+        let count = dimensions.cell_count();
+        if tiles.len() != count {
+            return Err(ParseError::WrongTileCount { expected: count, actual: tiles.len() });
+        }
+        let mut seen = vec![false; count];
+        for (index, &value) in tiles.iter().enumerate() {
+            if usize::from(value) >= count {
+                return Err(ParseError::OutOfRange { index, value });
+            }
+            if seen[usize::from(value)] {
+                return Err(ParseError::DuplicateTile { index, value });
+            }
+            seen[usize::from(value)] = true;
+        }
+        Ok(Goal::from_tiles(dimensions, tiles.to_vec()))
+    }
+
+    fn from_tiles(dimensions: Dimensions, tiles: Vec<TileValue>) -> Self {
+        let mut positions = vec![0; tiles.len()];
+        for (index, &value) in tiles.iter().enumerate() {
+            positions[usize::from(value)] = index as TileValue;
+        }
+        Goal { dimensions, tiles, positions }
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// Tiles row by row, `0` stands for the hole
+    pub fn tiles(&self) -> &[TileValue] {
+        &self.tiles
+    }
+
+    /// Target index of each value, the hole first
+    pub fn positions(&self) -> &[TileValue] {
+        &self.positions
+    }
+
+    /// Target index of a value, `0` stands for the hole
+    pub fn position(&self, value: TileValue) -> usize {
+        usize::from(self.positions[usize::from(value)])
+    }
+}
+
+/// The current layout of a taquin as a goal
+impl From<&Taquin> for Goal {
+    fn from(taquin: &Taquin) -> Self {
+        Goal::from_tiles(taquin.dimensions(), taquin.tile_values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted() {
+        assert_eq!(Goal::sorted(3).tiles(), &[1, 2, 3, 4, 5, 6, 7, 8, 0]);
+        assert_eq!(Goal::sorted(3).position(0), 8);
+        assert_eq!(Goal::sorted(3).position(1), 0);
+    }
+
+    #[test]
+    fn hole_first() {
+        assert_eq!(Goal::hole_first(2).tiles(), &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn spiral() {
+        assert_eq!(Goal::spiral(2).tiles(), &[1, 2, 0, 3]);
+        assert_eq!(Goal::spiral(3).tiles(), &[1, 2, 3, 8, 0, 4, 7, 6, 5]);
+        assert_eq!(Goal::spiral(4).tiles(), &[1, 2, 3, 4, 12, 13, 14, 5, 11, 0, 15, 6, 10, 9, 8, 7]);
+        assert_eq!(Goal::spiral(Dimensions::new(2, 4)).tiles(), &[1, 2, 3, 4, 0, 7, 6, 5]);
+        assert_eq!(Goal::spiral(Dimensions::new(3, 2)).tiles(), &[1, 2, 0, 3, 5, 4]);
+    }
+
+    #[test]
+    fn rectangle() {
+        let goal = Goal::sorted(Dimensions::new(2, 3));
+        assert_eq!(goal.tiles(), &[1, 2, 3, 4, 5, 0]);
+        assert_eq!(goal.position(0), 5);
+    }
+
+    mod custom {
+        use super::*;
+
+        #[test]
+        fn ok() {
+            let goal = Goal::custom(2, &[3, 0, 1, 2]).unwrap();
+            assert_eq!(goal.positions(), &[1, 2, 3, 0]);
+        }
+
+        #[test]
+        fn invalid() {
+            assert_eq!(Goal::custom(2, &[1, 2, 0]), Err(ParseError::WrongTileCount { expected: 4, actual: 3 }));
+            assert_eq!(Goal::custom(2, &[1, 2, 4, 0]), Err(ParseError::OutOfRange { index: 2, value: 4 }));
+            assert_eq!(Goal::custom(2, &[1, 2, 2, 0]), Err(ParseError::DuplicateTile { index: 2, value: 2 }));
+        }
+    }
+}
@@ -1,9 +1,67 @@
+use std::fmt::{self, Display, Formatter};
 use std::slice::Iter;
+use std::str::FromStr;
 
 pub type Row = u8;
 pub type Column = u8;
 pub type Size = u8;
 
+/// Number of rows and columns, a single size is a square
+#[derive(Hash, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dimensions {
+    rows: Row,
+    columns: Column,
+}
+
+impl Dimensions {
+    pub fn new(rows: Row, columns: Column) -> Self {
+        Dimensions { rows, columns }
+    }
+
+    pub fn rows(&self) -> Row {
+        self.rows
+    }
+
+    pub fn columns(&self) -> Column {
+        self.columns
+    }
+
+    pub fn cell_count(&self) -> usize {
+        usize::from(self.rows) * usize::from(self.columns)
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.columns
+    }
+}
+
+impl From<Size> for Dimensions {
+    fn from(size: Size) -> Self {
+        Dimensions::new(size, size)
+    }
+}
+
+/// `4` for a square, `2x4` for 2 rows and 4 columns
+impl Display for Dimensions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_square() {
+            write!(f, "{}", self.rows)
+        } else {
+            write!(f, "{}x{}", self.rows, self.columns)
+        }
+    }
+}
+
+impl FromStr for Dimensions {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('x') {
+            Some((rows, columns)) => Ok(Dimensions::new(rows.trim().parse()?, columns.trim().parse()?)),
+            None => s.trim().parse::<Size>().map(Dimensions::from),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Position {
@@ -28,21 +86,30 @@ impl Position {
 
 #[derive(Hash, Clone, Debug, PartialEq, Eq)]
 pub struct Grid<T> where T: PartialEq + Clone {
-    size: Size,
+    dimensions: Dimensions,
     content: Vec<T>,
 }
 
 impl<T> Grid<T> where T: PartialEq + Clone {
-    pub fn new(size: Size, content: Vec<T>) -> Self {
-        if content.len() != (size * size) as usize {
-            panic!("Content should have a size of {}, got {}", (size * size), content.len())
+    pub fn new<D: Into<Dimensions>>(dimensions: D, content: Vec<T>) -> Self {
+        let dimensions = dimensions.into();
+        if content.len() != dimensions.cell_count() {
+            panic!("Content should have a size of {}, got {}", dimensions.cell_count(), content.len())
         }
 
-        Grid { size, content }
+        Grid { dimensions, content }
     }
 
-    pub fn size(&self) -> Size {
-        self.size
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    pub fn rows(&self) -> Row {
+        self.dimensions.rows
+    }
+
+    pub fn columns(&self) -> Column {
+        self.dimensions.columns
     }
 
     pub fn iter(&self) -> Iter<'_, T> {
@@ -50,11 +117,9 @@ impl<T> Grid<T> where T: PartialEq + Clone {
     }
 
     pub fn index_to_position(&self, index: usize) -> Option<Position> {
-        let row = index as Size / self.size;
-        let column = index as Size % self.size;
-
-        if row < self.size {
-            Some(Position::new(row, column))
+        let columns = usize::from(self.columns());
+        if index < self.content.len() {
+            Some(Position::new((index / columns) as Row, (index % columns) as Column))
         } else {
             None
         }
@@ -63,8 +128,8 @@ impl<T> Grid<T> where T: PartialEq + Clone {
     fn position_to_index(&self, position: &Position) -> Option<usize> {
         let row = position.row();
         let column = position.column();
-        if row < self.size && column < self.size {
-            Some((row * self.size + column) as usize)
+        if row < self.rows() && column < self.columns() {
+            Some(usize::from(row) * usize::from(self.columns()) + usize::from(column))
         } else {
             None
         }
@@ -101,10 +166,31 @@ mod tests {
 
         pub(crate) fn a_grid() -> Grid<i32> {
             Grid {
-                size: 2,
+                dimensions: Dimensions::from(2),
                 content: vec![1, 2, 3, 4],
             }
         }
+
+        pub(crate) fn a_rectangle() -> Grid<i32> {
+            Grid::new(Dimensions::new(2, 3), vec![1, 2, 3, 4, 5, 6])
+        }
+    }
+
+    mod dimensions {
+        use super::*;
+
+        #[test]
+        fn parse() {
+            assert_eq!("3".parse(), Ok(Dimensions::new(3, 3)));
+            assert_eq!("2x4".parse(), Ok(Dimensions::new(2, 4)));
+            assert!("2x".parse::<Dimensions>().is_err());
+        }
+
+        #[test]
+        fn display() {
+            assert_eq!(Dimensions::from(3).to_string(), "3");
+            assert_eq!(Dimensions::new(3, 5).to_string(), "3x5");
+        }
     }
 
     mod index_to_position {
@@ -123,6 +209,19 @@ mod tests {
 
             assert_eq!(pos, None)
         }
+
+        #[test]
+        fn large() {
+            let grid = Grid::new(32, vec![0; 1024]);
+            assert_eq!(grid.index_to_position(1023), Some(Position::new(31, 31)));
+            assert_eq!(grid.position_to_index(&Position::new(31, 31)), Some(1023));
+        }
+
+        #[test]
+        fn rectangle() {
+            assert_eq!(given::a_rectangle().index_to_position(4), Some(Position::new(1, 1)));
+            assert_eq!(given::a_rectangle().index_to_position(6), None);
+        }
     }
 
     mod position_to_index {
@@ -143,6 +242,12 @@ mod tests {
 
             assert_eq!(index, None)
         }
+
+        #[test]
+        fn rectangle() {
+            assert_eq!(given::a_rectangle().position_to_index(&Position::new(1, 2)), Some(5));
+            assert_eq!(given::a_rectangle().position_to_index(&Position::new(0, 3)), None);
+        }
     }
 
     mod find {
@@ -0,0 +1,337 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::goal::Goal;
+use crate::grid::Dimensions;
+use crate::packed::PackedTaquin;
+use crate::solver::Heuristic;
+use crate::taquin::{Taquin, TileValue};
+
+/// Manhattan distance
+///
+/// Each tile needs at least as many moves as its distance to its goal position.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ManhattanDistance;
+
+impl Heuristic<Taquin> for ManhattanDistance {
+    fn estimate(&self, taquin: &Taquin) -> u32 {
+        taquin.manhattan_distance()
+    }
+}
+
+impl Heuristic<PackedTaquin> for ManhattanDistance {
+    fn estimate(&self, taquin: &PackedTaquin) -> u32 {
+        taquin.manhattan_distance()
+    }
+}
+
+/// Manhattan distance with linear conflicts
+///
+/// Two tiles in their goal row (or column) but in the reverse order cannot pass each other,
+/// one of them has to leave the line and come back, that's 2 more moves.
+/// For each line, the tiles that stay form the longest increasing sequence of goal positions,
+/// all the others should leave.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinearConflict;
+
+impl LinearConflict {
+    /// The positions are the goal index of each value
+    fn conflicts(dimensions: Dimensions, values: &[TileValue], positions: &[TileValue]) -> u32 {
+        let rows = usize::from(dimensions.rows());
+        let columns = usize::from(dimensions.columns());
+        let target = |value: TileValue| usize::from(positions[usize::from(value)]);
+        let mut moves = 0;
+        for row in 0..rows {
+            // goal columns of the tiles of this row in their goal row, from left to right
+            let line: Vec<usize> = (0..columns)
+                .map(|column| values[row * columns + column])
+                .filter(|&value| value != 0 && target(value) / columns == row)
+                .map(|value| target(value) % columns)
+                .collect();
+            moves += 2 * (line.len() - longest_increasing(&line));
+        }
+        for column in 0..columns {
+            // goal rows of the tiles of this column in their goal column, from top to bottom
+            let line: Vec<usize> = (0..rows)
+                .map(|row| values[row * columns + column])
+                .filter(|&value| value != 0 && target(value) % columns == column)
+                .map(|value| target(value) / columns)
+                .collect();
+            moves += 2 * (line.len() - longest_increasing(&line));
+        }
+        moves as u32
+    }
+}
+
+impl Heuristic<Taquin> for LinearConflict {
+    fn estimate(&self, taquin: &Taquin) -> u32 {
+        let positions = taquin.target().positions();
+        taquin.manhattan_distance() + LinearConflict::conflicts(taquin.dimensions(), &taquin.tile_values(), positions)
+    }
+}
+
+impl Heuristic<PackedTaquin> for LinearConflict {
+    fn estimate(&self, taquin: &PackedTaquin) -> u32 {
+        let positions = taquin.goal_positions();
+        taquin.manhattan_distance() + LinearConflict::conflicts(taquin.dimensions(), &taquin.tile_values(), &positions)
+    }
+}
+
+fn longest_increasing(values: &[usize]) -> usize {
+    let mut lengths: Vec<usize> = vec![];
+    for (i, value) in values.iter().enumerate() {
+        let length = (0..i)
+            .filter(|&j| values[j] < *value)
+            .map(|j| lengths[j] + 1)
+            .max()
+            .unwrap_or(1);
+        lengths.push(length);
+    }
+    lengths.into_iter().max().unwrap_or(0)
+}
+
+/// Walking distance
+///
+/// Vertically, a taquin is summed up by the number of tiles of each row going to each goal row.
+/// There are as many lines as rows, each line holds as many tiles as columns.
+/// A vertical move takes a tile from a row next to the hole into the row of the hole,
+/// a BFS from the goal table gives the number of vertical moves needed from any table.
+/// The columns give the horizontal moves the same way, so the sum is admissible.
+///
+/// The tables are built for a goal, and the number of tables grows quickly with the size,
+/// it's meant for up to 4x4 taquins.
+#[derive(Clone, Debug)]
+pub struct WalkingDistance {
+    goal: Goal,
+    rows: HashMap<Vec<u8>, u8>,
+    columns: HashMap<Vec<u8>, u8>,
+}
+
+impl WalkingDistance {
+    /// Tables for the sorted goal
+    pub fn new<D: Into<Dimensions>>(dimensions: D) -> Self {
+        WalkingDistance::for_goal(Goal::sorted(dimensions))
+    }
+
+    pub fn for_goal(goal: Goal) -> Self {
+        let rows = usize::from(goal.dimensions().rows());
+        let columns = usize::from(goal.dimensions().columns());
+        let hole = goal.position(0);
+        let (hole_row, hole_column) = (hole / columns, hole % columns);
+        let row_distances = WalkingDistance::build(rows, columns, hole_row);
+        let column_distances = if rows == columns && hole_row == hole_column {
+            row_distances.clone()
+        } else {
+            WalkingDistance::build(columns, rows, hole_column)
+        };
+
+        WalkingDistance { goal, rows: row_distances, columns: column_distances }
+    }
+
+    /// BFS from the goal table of `lines` lines of `capacity` tiles, with the hole in the given line
+    fn build(lines: usize, capacity: usize, hole_line: usize) -> HashMap<Vec<u8>, u8> {
+        let size = lines;
+        let mut goal = vec![0; size * size];
+        for line in 0..size {
+            goal[line * size + line] = capacity as u8;
+        }
+        goal[hole_line * size + hole_line] -= 1;
+
+        let mut distances = HashMap::new();
+        distances.insert(goal.clone(), 0);
+        let mut queue = VecDeque::new();
+        queue.push_back(goal);
+        while let Some(table) = queue.pop_front() {
+            let distance = distances[&table];
+            let hole_line = WalkingDistance::hole_line(size, capacity, &table);
+            let next_lines = [hole_line.checked_sub(1), Some(hole_line + 1).filter(|&line| line < size)];
+            for &line in next_lines.iter().flatten() {
+                for goal_line in 0..size {
+                    if table[line * size + goal_line] == 0 { continue; }
+                    let mut next = table.clone();
+                    next[line * size + goal_line] -= 1;
+                    next[hole_line * size + goal_line] += 1;
+                    if !distances.contains_key(&next) {
+                        distances.insert(next.clone(), distance + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.goal.dimensions()
+    }
+
+    pub fn goal(&self) -> &Goal {
+        &self.goal
+    }
+
+    /// Number of row tables reachable from the goal
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The line with a missing tile
+    fn hole_line(size: usize, capacity: usize, table: &[u8]) -> usize {
+        (0..size)
+            .find(|&line| table[line * size..(line + 1) * size].iter().sum::<u8>() < capacity as u8)
+            .expect("A line should have the hole")
+    }
+
+    fn estimate_values(&self, dimensions: Dimensions, values: &[TileValue]) -> u32 {
+        assert_eq!(dimensions, self.dimensions(), "Walking distance tables built for another size");
+        let row_count = usize::from(dimensions.rows());
+        let column_count = usize::from(dimensions.columns());
+        let mut rows = vec![0; row_count * row_count];
+        let mut columns = vec![0; column_count * column_count];
+        for (index, &value) in values.iter().enumerate() {
+            if value == 0 { continue; }
+            let target = self.goal.position(value);
+            rows[index / column_count * row_count + target / column_count] += 1;
+            columns[index % column_count * column_count + target % column_count] += 1;
+        }
+        u32::from(self.rows[&rows]) + u32::from(self.columns[&columns])
+    }
+}
+
+/// The taquin should have the goal of the tables
+impl Heuristic<Taquin> for WalkingDistance {
+    fn estimate(&self, taquin: &Taquin) -> u32 {
+        debug_assert_eq!(taquin.target(), &self.goal);
+        self.estimate_values(taquin.dimensions(), &taquin.tile_values())
+    }
+}
+
+/// The taquin should have the goal of the tables
+impl Heuristic<PackedTaquin> for WalkingDistance {
+    fn estimate(&self, taquin: &PackedTaquin) -> u32 {
+        debug_assert_eq!(taquin.target(), self.goal);
+        self.estimate_values(taquin.dimensions(), &taquin.tile_values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    mod given {
+        use super::*;
+
+        pub fn a_taquin() -> Taquin {
+            Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6")
+        }
+    }
+
+    #[test]
+    fn longest_increasing_sequence() {
+        assert_eq!(longest_increasing(&[]), 0);
+        assert_eq!(longest_increasing(&[2, 0, 1]), 2);
+        assert_eq!(longest_increasing(&[3, 2, 1, 0]), 1);
+        assert_eq!(longest_increasing(&[0, 3, 1, 2]), 3);
+    }
+
+    mod linear_conflict {
+        use super::*;
+
+        #[test]
+        fn solved() {
+            assert_eq!(LinearConflict.estimate(&Taquin::new(4)), 0);
+        }
+
+        #[test]
+        fn conflicts() {
+            // 2 and 1 are in their goal row in the reverse order
+            let taquin = Taquin::from_str(3, "2,1,3,  4,5,6,  7,0,8");
+            assert_eq!(LinearConflict.estimate(&taquin), taquin.manhattan_distance() + 2);
+
+            // 3, 2 and 1 are reversed, two of them leave the row
+            let taquin = Taquin::from_str(3, "3,2,1,  4,5,6,  7,8,0");
+            assert_eq!(LinearConflict.estimate(&taquin), taquin.manhattan_distance() + 4);
+        }
+
+        #[test]
+        fn packed() {
+            let taquin = given::a_taquin();
+            let packed = PackedTaquin::try_from(&taquin).unwrap();
+            assert_eq!(LinearConflict.estimate(&packed), LinearConflict.estimate(&taquin));
+        }
+
+        #[test]
+        fn with_goal() {
+            // 6 and 7 are in their goal row of the spiral goal in the reverse order
+            let taquin = Taquin::from_str(3, "1,2,3,  8,0,4,  6,7,5").with_goal(Goal::spiral(3));
+            assert_eq!(LinearConflict.estimate(&Taquin::solved(Goal::spiral(3))), 0);
+            assert_eq!(LinearConflict.estimate(&taquin), taquin.manhattan_distance() + 2);
+
+            let packed = PackedTaquin::try_from(&taquin).unwrap();
+            assert_eq!(LinearConflict.estimate(&packed), LinearConflict.estimate(&taquin));
+        }
+
+        #[test]
+        fn rectangle() {
+            // 5 and 4 are in their goal row in the reverse order
+            let taquin = Taquin::from_str(Dimensions::new(2, 3), "1,2,3,  5,4,0");
+            assert_eq!(LinearConflict.estimate(&taquin), taquin.manhattan_distance() + 2);
+
+            // 4 and 1 are in their goal column in the reverse order
+            let taquin = Taquin::from_str(Dimensions::new(3, 2), "4,2,  3,0,  1,5");
+            assert_eq!(LinearConflict.estimate(&taquin), taquin.manhattan_distance() + 2);
+        }
+    }
+
+    mod walking_distance {
+        use super::*;
+
+        #[test]
+        fn table_size() {
+            assert_eq!(WalkingDistance::new(3).len(), 105);
+            assert_eq!(WalkingDistance::new(4).len(), 24964);
+        }
+
+        #[test]
+        fn solved() {
+            assert_eq!(WalkingDistance::new(4).estimate(&Taquin::new(4)), 0);
+        }
+
+        #[test]
+        fn at_least_manhattan() {
+            let taquin = given::a_taquin();
+            assert!(WalkingDistance::new(3).estimate(&taquin) >= taquin.manhattan_distance());
+        }
+
+        #[test]
+        fn with_goal() {
+            let walking_distance = WalkingDistance::for_goal(Goal::hole_first(3));
+            assert_eq!(walking_distance.estimate(&Taquin::solved(Goal::hole_first(3))), 0);
+
+            let taquin = given::a_taquin().with_goal(Goal::hole_first(3));
+            assert!(walking_distance.estimate(&taquin) >= taquin.manhattan_distance());
+        }
+
+        #[test]
+        fn packed() {
+            let walking_distance = WalkingDistance::new(3);
+            let taquin = given::a_taquin();
+            let packed = PackedTaquin::try_from(&taquin).unwrap();
+            assert_eq!(walking_distance.estimate(&packed), walking_distance.estimate(&taquin));
+        }
+
+        #[test]
+        fn rectangle() {
+            let dimensions = Dimensions::new(2, 4);
+            let walking_distance = WalkingDistance::new(dimensions);
+            assert_eq!(walking_distance.estimate(&Taquin::new(dimensions)), 0);
+
+            let taquin = Taquin::from_str(dimensions, "5,1,3,4,  2,0,6,7");
+            assert!(walking_distance.estimate(&taquin) >= taquin.manhattan_distance());
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::convert::TryFrom;
+use std::io::{BufRead, Result};
+
+use crate::grid::Dimensions;
+use crate::taquin::{ParseError, Taquin};
+
+/// A parsed line with its number, starting at 1
+pub type Line = (usize, std::result::Result<Taquin, ParseError>);
+
+/// Read taquins, one per line
+///
+/// * empty lines and lines starting with `#` are ignored,
+/// * a `size: 4` or `size: 3x5` header sets the size of the following lines,
+/// * a `4: 1,2,3,...` or `2x4: 1,2,3,...` prefix sets the size of the line only,
+/// * otherwise the taquin is square, its size is inferred from the number of tiles.
+pub fn read_taquins<R: BufRead>(reader: R) -> Result<Vec<Line>> {
+    let mut size = None;
+    let mut result = vec![];
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+
+        if let Some(header) = line.strip_prefix("size:") {
+            match parse_size(header) {
+                Ok(header_size) => size = Some(header_size),
+                Err(error) => result.push((index + 1, Err(error))),
+            }
+            continue;
+        }
+
+        result.push((index + 1, parse_line(size, line)));
+    }
+    Ok(result)
+}
+
+/// One taquin, with an optional `4:` or `2x4:` prefix, otherwise it is square
+pub fn parse_taquin(line: &str) -> std::result::Result<Taquin, ParseError> {
+    parse_line(None, line.trim())
+}
+
+fn parse_line(size: Option<Dimensions>, line: &str) -> std::result::Result<Taquin, ParseError> {
+    match line.split_once(':') {
+        Some((line_size, tiles)) => Taquin::try_from((parse_size(line_size)?, tiles)),
+        None => match size {
+            Some(size) => Taquin::try_from((size, line)),
+            None => line.parse(),
+        },
+    }
+}
+
+fn parse_size(str: &str) -> std::result::Result<Dimensions, ParseError> {
+    let str = str.trim();
+    str.parse()
+        .map_err(|_| ParseError::InvalidSizeDeclaration(str.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(input: &str) -> Vec<Line> {
+        read_taquins(input.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn infer_size() {
+        let lines = read("1,2,3,  4,5,6,  7,8,0\n\n1,2,3,4,  5,6,7,8,  9,10,11,12,  13,14,15,0\n");
+        assert_eq!(lines, vec![(1, Ok(Taquin::new(3))), (3, Ok(Taquin::new(4)))]);
+    }
+
+    #[test]
+    fn declared_size() {
+        let lines = read("# comment\n3: 1,2,3,  4,5,6,  7,8,0\n");
+        assert_eq!(lines, vec![(2, Ok(Taquin::new(3)))]);
+    }
+
+    #[test]
+    fn header_size() {
+        let lines = read("size: 2\n1,2,3,0\nsize: 3\n1,2,3,  4,5,6,  7,8,0\n");
+        assert_eq!(lines, vec![(2, Ok(Taquin::new(2))), (4, Ok(Taquin::new(3)))]);
+    }
+
+    #[test]
+    fn rectangle() {
+        let lines = read("2x4: 1,2,3,4,  5,6,7,0\nsize: 3x2\n1,2,  3,4,  5,0\n");
+        assert_eq!(lines, vec![
+            (1, Ok(Taquin::new(Dimensions::new(2, 4)))),
+            (3, Ok(Taquin::new(Dimensions::new(3, 2)))),
+        ]);
+    }
+
+    #[test]
+    fn header_size_mismatch() {
+        let lines = read("size: 4\n1,2,3,  4,5,6,  7,8,0\n");
+        assert_eq!(lines, vec![(2, Err(ParseError::WrongTileCount { expected: 16, actual: 9 }))]);
+    }
+
+    #[test]
+    fn single_taquin() {
+        assert_eq!(parse_taquin(" 2x4: 1,2,3,4,  5,6,7,0 "), Ok(Taquin::new(Dimensions::new(2, 4))));
+        assert_eq!(parse_taquin("1,2,3,  4,5,6,  7,8,0"), Ok(Taquin::new(3)));
+    }
+
+    #[test]
+    fn invalid_size() {
+        let lines = read("three: 1,2,3,  4,5,6,  7,8,0\n");
+        assert_eq!(lines, vec![(1, Err(ParseError::InvalidSizeDeclaration(String::from("three"))))]);
+    }
+}
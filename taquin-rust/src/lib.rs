@@ -1,4 +1,14 @@
+pub mod goal;
 pub mod grid;
 pub mod taquin;
+pub mod packed;
 pub mod solver;
-
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod heuristic;
+pub mod pattern;
+pub mod constructive;
+pub mod distance;
+pub mod input;
+pub mod random;
+pub mod permutation;
@@ -1,68 +1,759 @@
-use std::env::args;
-use taquin_rust::taquin::Taquin;
-use taquin_rust::solver::Problem;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{BufReader, BufRead, Error};
+use std::io::{BufReader, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use structopt::StructOpt;
+
+use taquin_rust::distance::DistanceDatabase;
+use taquin_rust::goal::Goal;
+use taquin_rust::grid::Dimensions;
+use taquin_rust::input;
+use taquin_rust::heuristic::{LinearConflict, ManhattanDistance, WalkingDistance};
+use taquin_rust::packed::PackedTaquin;
+use taquin_rust::pattern::PatternDatabase;
+use taquin_rust::random::SplitMix64;
+use taquin_rust::solver::{Heuristic, Limits, Monitor, ReversibleProblem, SolverError, Stats};
+use taquin_rust::taquin::{Move, Taquin, TileValue};
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "taquin", about = "Sliding puzzle solver")]
+enum Command {
+    /// Solve taquins
+    Solve {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        options: SolveOptions,
+    },
+    /// Generate random taquins, one per line
+    Generate {
+        /// Size of the taquins, `3` or rows by columns like `2x4`
+        #[structopt(short, long, default_value = "3")]
+        size: Dimensions,
+        /// Number of taquins
+        #[structopt(short = "n", long, default_value = "10")]
+        count: u32,
+        /// Number of random moves from the solved taquin
+        #[structopt(short, long, default_value = "50")]
+        moves: u32,
+        /// Goal of the taquins, see `solve --help`
+        #[structopt(short, long, default_value = "sorted")]
+        goal: GoalOption,
+        /// Draw uniformly among the solvable taquins instead of applying random moves
+        #[structopt(short, long)]
+        uniform: bool,
+        /// Seed of the random taquins, printed in a comment to generate the same taquins again
+        #[structopt(long)]
+        seed: Option<u64>,
+    },
+    /// Generate taquins whose optimal solution has an exact number of moves, one per line
+    Exact {
+        /// Size of the taquins, `3` or rows by columns like `2x4`
+        #[structopt(short, long, default_value = "3")]
+        size: Dimensions,
+        /// Number of moves of the optimal solution
+        #[structopt(short, long)]
+        length: u32,
+        /// Number of taquins, fewer when there are not enough taquins at this distance
+        #[structopt(short = "n", long, default_value = "10")]
+        count: usize,
+        /// Goal of the taquins, see `solve --help`
+        #[structopt(short, long, default_value = "sorted")]
+        goal: GoalOption,
+        /// Seed of the random taquins, printed in a comment to generate the same taquins again
+        #[structopt(long)]
+        seed: Option<u64>,
+        /// Give up when the search keeps more than this number of states
+        #[structopt(long)]
+        max_states: Option<usize>,
+    },
+    /// Count the 3x3 taquins at each distance of the goal
+    Distances {
+        /// Goal of the taquins, see `solve --help`
+        #[structopt(short, long, default_value = "sorted")]
+        goal: GoalOption,
+    },
+    /// Check that taquins are solvable
+    Check {
+        #[structopt(flatten)]
+        input: Input,
+    },
+    /// Verify that a sequence of moves solves a taquin
+    Verify {
+        /// Tiles, comma separated, 0 for the hole, with an optional `2x4:` prefix
+        #[structopt(short, long)]
+        tiles: String,
+        /// Moves as letters, e.g. `UURDL`
+        moves: String,
+        /// Goal of the taquin, see `solve --help`
+        #[structopt(short, long, default_value = "sorted")]
+        goal: GoalOption,
+    },
+    /// Measure the solving time
+    Bench {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        options: SolveOptions,
+    },
+    /// Build the pattern database of a size, with its usual partition
+    Pdb {
+        /// Size of the taquins, `4` or rows by columns like `3x5`
+        #[structopt(short, long, default_value = "4")]
+        size: Dimensions,
+        /// Output file
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+        /// Goal of the taquins, see `solve --help`
+        #[structopt(short, long, default_value = "sorted")]
+        goal: GoalOption,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+struct Input {
+    /// File with one taquin per line
+    #[structopt(parse(from_os_str))]
+    file: Option<PathBuf>,
+    /// Inline tiles instead of a file, comma separated, 0 for the hole
+    #[structopt(short, long, conflicts_with = "file")]
+    tiles: Option<String>,
+    /// Goal of the taquins: sorted, hole-first, spiral, or comma separated tiles with an optional `2x4:` prefix
+    #[structopt(short, long, default_value = "sorted")]
+    goal: GoalOption,
+}
+
+#[derive(Debug, Clone)]
+enum GoalOption {
+    Sorted,
+    HoleFirst,
+    Spiral,
+    Custom(Goal),
+}
+
+impl GoalOption {
+    fn goal(&self, dimensions: Dimensions) -> Result<Goal, String> {
+        match self {
+            GoalOption::Sorted => Ok(Goal::sorted(dimensions)),
+            GoalOption::HoleFirst => Ok(Goal::hole_first(dimensions)),
+            GoalOption::Spiral => Ok(Goal::spiral(dimensions)),
+            GoalOption::Custom(goal) if goal.dimensions() == dimensions => Ok(goal.clone()),
+            GoalOption::Custom(goal) => Err(format!("goal has size {}, expected {}", goal.dimensions(), dimensions)),
+        }
+    }
+}
+
+impl FromStr for GoalOption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sorted" => Ok(GoalOption::Sorted),
+            "hole-first" => Ok(GoalOption::HoleFirst),
+            "spiral" => Ok(GoalOption::Spiral),
+            tiles => input::parse_taquin(tiles)
+                .map(|taquin| GoalOption::Custom(Goal::from(&taquin)))
+                .map_err(|error| format!("Invalid goal '{}': {}", s, error)),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct SolveOptions {
+    /// Search algorithm
+    #[structopt(short, long, default_value = "astar", possible_values = &Algorithm::NAMES)]
+    algorithm: Algorithm,
+    /// Output format
+    #[structopt(short, long, default_value = "text", possible_values = &Format::NAMES)]
+    format: Format,
+    /// Give up a taquin after this number of seconds
+    #[structopt(long)]
+    timeout: Option<u64>,
+    /// Give up a taquin after expanding this number of states
+    #[structopt(long)]
+    max_expanded: Option<u64>,
+    /// Number of taquins solved concurrently
+    #[structopt(short, long, default_value = "1")]
+    jobs: usize,
+    /// Weight of the heuristic for `weighted-astar` and `anytime`, solutions are at most this many times longer
+    #[structopt(short, long, default_value = "2", parse(try_from_str = parse_weight))]
+    weight: f64,
+    /// Heuristic of A* and IDA*
+    #[structopt(long, default_value = "manhattan", possible_values = &HeuristicName::NAMES)]
+    heuristic: HeuristicName,
+    /// Pattern database file of the `pdb` heuristic, see the `pdb` command
+    #[structopt(long, parse(from_os_str), required_if("heuristic", "pdb"))]
+    pdb: Option<PathBuf>,
+    /// Post-process the moves with a path-shortening pass, useful with `constructive`
+    ///
+    /// The pass shares the limits of the search, the moves are kept as they are once a limit is reached.
+    #[structopt(long)]
+    shorten: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Algorithm {
+    Bfs,
+    #[cfg(feature = "parallel")]
+    ParallelBfs,
+    Bidirectional,
+    AStar,
+    IdaStar,
+    WeightedAStar,
+    Anytime,
+    Constructive,
+}
+
+impl Algorithm {
+    #[cfg(not(feature = "parallel"))]
+    const NAMES: [&'static str; 7] = ["bfs", "bidir", "astar", "idastar", "weighted-astar", "anytime", "constructive"];
+    #[cfg(feature = "parallel")]
+    const NAMES: [&'static str; 8] = ["bfs", "parallel-bfs", "bidir", "astar", "idastar", "weighted-astar", "anytime", "constructive"];
+
+    /// Solve the packed taquin when it fits, the moves are the same
+    fn solve<M>(self, taquin: &Taquin, options: &SolveOptions, heuristic: &TaquinHeuristic, monitor: &mut M) -> Result<Vec<Move>, SolverError>
+        where M: Monitor<Move> {
+        if let Algorithm::Constructive = self {
+            return taquin.solve_constructive_with_monitor(monitor);
+        }
+        match PackedTaquin::try_from(taquin) {
+            Ok(packed) => self.solve_problem(&packed, options, heuristic, monitor),
+            Err(_) => self.solve_problem(taquin, options, heuristic, monitor),
+        }
+    }
+
+    fn solve_problem<P, M>(self, problem: &P, options: &SolveOptions, heuristic: &TaquinHeuristic, monitor: &mut M) -> Result<Vec<Move>, SolverError>
+        where P: ReversibleProblem<Move> + Send + Sync, TaquinHeuristic: Heuristic<P>, M: Monitor<Move> {
+        match self {
+            Algorithm::Bfs => problem.solve_with_monitor(monitor),
+            #[cfg(feature = "parallel")]
+            Algorithm::ParallelBfs => problem.solve_parallel_with_monitor(monitor),
+            Algorithm::Bidirectional => problem.solve_bidirectional_with_monitor(monitor),
+            Algorithm::AStar => problem.solve_astar_with_monitor(heuristic, monitor),
+            Algorithm::IdaStar => problem.solve_idastar_with_monitor(heuristic, monitor),
+            Algorithm::WeightedAStar => problem.solve_weighted_astar_with_monitor(heuristic, options.weight, monitor),
+            // The last solution is kept when the timeout is reached
+            Algorithm::Anytime => problem.solve_anytime_with_monitor(heuristic, options.weight, monitor, |steps| {
+                if let Format::Text = options.format {
+                    eprintln!("Improved solution: {} steps", steps.len());
+                }
+            }),
+            Algorithm::Constructive => unreachable!("The constructive solver only solves a taquin"),
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bfs" => Ok(Algorithm::Bfs),
+            #[cfg(feature = "parallel")]
+            "parallel-bfs" => Ok(Algorithm::ParallelBfs),
+            "bidir" => Ok(Algorithm::Bidirectional),
+            "astar" => Ok(Algorithm::AStar),
+            "idastar" => Ok(Algorithm::IdaStar),
+            "weighted-astar" => Ok(Algorithm::WeightedAStar),
+            "anytime" => Ok(Algorithm::Anytime),
+            "constructive" => Ok(Algorithm::Constructive),
+            _ => Err(format!("Unknown algorithm '{}'", s)),
+        }
+    }
+}
+
+/// Finite and at least 1, the bound on the solution length
+fn parse_weight(s: &str) -> Result<f64, String> {
+    let weight: f64 = s.parse().map_err(|_| format!("Invalid weight '{}'", s))?;
+    if weight.is_finite() && weight >= 1.0 {
+        Ok(weight)
+    } else {
+        Err(format!("Weight should be a finite number at least 1, got {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HeuristicName {
+    Manhattan,
+    LinearConflict,
+    WalkingDistance,
+    PatternDatabase,
+    Table,
+}
+
+impl HeuristicName {
+    const NAMES: [&'static str; 5] = ["manhattan", "linear-conflict", "walking-distance", "pdb", "table"];
+}
+
+impl FromStr for HeuristicName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manhattan" => Ok(HeuristicName::Manhattan),
+            "linear-conflict" => Ok(HeuristicName::LinearConflict),
+            "walking-distance" => Ok(HeuristicName::WalkingDistance),
+            "pdb" => Ok(HeuristicName::PatternDatabase),
+            "table" => Ok(HeuristicName::Table),
+            _ => Err(format!("Unknown heuristic '{}'", s)),
+        }
+    }
+}
+
+enum TaquinHeuristic {
+    Manhattan,
+    LinearConflict,
+    WalkingDistance(WalkingDistance),
+    PatternDatabase(PatternDatabase),
+    Table(DistanceDatabase),
+}
+
+impl TaquinHeuristic {
+    /// Build the tables once, they should match the goal of all the taquins
+    fn load(options: &SolveOptions, taquins: &[Taquin]) -> Result<Self, Error> {
+        match options.heuristic {
+            HeuristicName::Manhattan => Ok(TaquinHeuristic::Manhattan),
+            HeuristicName::LinearConflict => Ok(TaquinHeuristic::LinearConflict),
+            HeuristicName::WalkingDistance => {
+                let goal = taquins.first().map_or_else(|| Goal::sorted(3), |taquin| taquin.target().clone());
+                check_goal(taquins, &goal, "walking distance")?;
+                Ok(TaquinHeuristic::WalkingDistance(WalkingDistance::for_goal(goal)))
+            }
+            HeuristicName::PatternDatabase => {
+                let filename = options.pdb.as_ref().expect("Pattern database file is required");
+                let database = PatternDatabase::load(filename)?;
+                check_goal(taquins, database.goal(), &filename.display().to_string())?;
+                Ok(TaquinHeuristic::PatternDatabase(database))
+            }
+            HeuristicName::Table => {
+                let goal = taquins.first().map_or_else(|| Goal::sorted(3), |taquin| taquin.target().clone());
+                if goal.dimensions() != Dimensions::from(3) {
+                    return Err(Error::new(ErrorKind::InvalidInput, "the distance table only solves 3x3 taquins"));
+                }
+                check_goal(taquins, &goal, "distance table")?;
+                Ok(TaquinHeuristic::Table(DistanceDatabase::build_for_goal(goal)))
+            }
+        }
+    }
+}
+
+fn check_goal(taquins: &[Taquin], goal: &Goal, name: &str) -> Result<(), Error> {
+    match taquins.iter().find(|taquin| taquin.target() != goal) {
+        Some(taquin) => {
+            let message = format!("{} is built for the goal {}, cannot solve {}",
+                                  name, Taquin::solved(goal.clone()).to_line(), taquin.to_line());
+            Err(Error::new(ErrorKind::InvalidInput, message))
+        }
+        None => Ok(()),
+    }
+}
+
+impl<P> Heuristic<P> for TaquinHeuristic
+    where ManhattanDistance: Heuristic<P>, LinearConflict: Heuristic<P>,
+          WalkingDistance: Heuristic<P>, PatternDatabase: Heuristic<P>, DistanceDatabase: Heuristic<P> {
+    fn estimate(&self, problem: &P) -> u32 {
+        match self {
+            TaquinHeuristic::Manhattan => ManhattanDistance.estimate(problem),
+            TaquinHeuristic::LinearConflict => LinearConflict.estimate(problem),
+            TaquinHeuristic::WalkingDistance(walking_distance) => walking_distance.estimate(problem),
+            TaquinHeuristic::PatternDatabase(database) => database.estimate(problem),
+            TaquinHeuristic::Table(database) => database.estimate(problem),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Text,
+    Moves,
+    Json,
+    Csv,
+}
+
+impl Format {
+    const NAMES: [&'static str; 4] = ["text", "moves", "json", "csv"];
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "moves" => Ok(Format::Moves),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format '{}'", s)),
+        }
+    }
+}
 
 fn main() {
-    let args = args().collect::<Vec<String>>();
-    let filename = if args.len() > 1 { args[1].clone() } else { String::from("./taquin_3x3.txt") };
+    let result = match Command::from_args() {
+        Command::Solve { input, options } => read_taquins(&input)
+            .and_then(|taquins| {
+                let heuristic = TaquinHeuristic::load(&options, &taquins)?;
+                solve_taquins(taquins, &options, &heuristic);
+                Ok(())
+            }),
+        Command::Generate { size, count, moves, goal, uniform, seed } => {
+            let moves = if uniform { None } else { Some(moves) };
+            generate_taquins(size, count, moves, &goal, seed)
+        }
+        Command::Exact { size, length, count, goal, seed, max_states } =>
+            generate_exact_taquins(size, length, count, &goal, seed, max_states),
+        Command::Distances { goal } => count_distances(&goal),
+        Command::Check { input } => read_taquins(&input)
+            .map(check_taquins),
+        Command::Verify { tiles, moves, goal } => verify_moves(&tiles, &moves, &goal),
+        Command::Bench { input, options } => read_taquins(&input)
+            .and_then(|taquins| {
+                let heuristic = TaquinHeuristic::load(&options, &taquins)?;
+                bench_taquins(taquins, &options, &heuristic);
+                Ok(())
+            }),
+        Command::Pdb { size, output, goal } => build_pattern_database(size, &output, &goal),
+    };
 
-    let taquins = read_taquins(filename).unwrap();
-    println!("Found {} taquins to solve", taquins.len());
-    for taquin in taquins {
-        solve_taquin(taquin);
-        println!();
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
     }
 }
 
-fn read_taquins(filename: String) -> Result<Vec<Taquin>, Error> {
-    println!("Reading {}", filename);
-    let file = File::open(filename)?;
-    let buffer = BufReader::new(file);
-    let taquins: Vec<Taquin> = buffer.lines()
-        .map(|res| res.unwrap())
-        .filter(|line| !line.is_empty())
-        .map(|line| Taquin::from_str(3, line.as_str()))
-        .collect();
+fn read_taquins(input: &Input) -> Result<Vec<Taquin>, Error> {
+    let (name, lines) = match (&input.tiles, &input.file) {
+        (Some(tiles), _) => (String::from("tiles"), input::read_taquins(tiles.as_bytes())?),
+        (None, file) => {
+            let filename = file.clone().unwrap_or_else(|| PathBuf::from("./taquin_3x3.txt"));
+            let file = File::open(&filename)?;
+            (filename.display().to_string(), input::read_taquins(BufReader::new(file))?)
+        }
+    };
+
+    let mut taquins = vec![];
+    for (line, taquin) in lines {
+        let taquin = taquin.map_err(|error| error.to_string())
+            .and_then(|taquin| input.goal.goal(taquin.dimensions()).map(|goal| taquin.with_goal(goal)));
+        match taquin {
+            Ok(taquin) => taquins.push(taquin),
+            Err(error) => eprintln!("{}:{}: {}", name, line, error),
+        }
+    }
     Ok(taquins)
 }
 
+struct Solution {
+    result: Result<Vec<Move>, SolverError>,
+    stats: Stats,
+    elapsed: Duration,
+}
+
+impl Solution {
+    fn status(&self) -> &'static str {
+        match self.result {
+            Ok(_) => "solved",
+            Err(SolverError::Unsolvable) => "unsolvable",
+            Err(SolverError::NoSolutionFound) => "no_solution",
+            Err(SolverError::LimitReached { .. }) => "limit_reached",
+            Err(SolverError::Cancelled) => "cancelled",
+        }
+    }
 
-fn solve_taquin(taquin: Taquin) {
+    fn moves(&self) -> Option<&[Move]> {
+        self.result.as_deref().ok()
+    }
+}
+
+fn solve_with_limits(taquin: &Taquin, options: &SolveOptions, heuristic: &TaquinHeuristic) -> Solution {
+    let start = Instant::now();
+    let mut limits = Limits::new();
+    if let Some(timeout) = options.timeout {
+        limits = limits.deadline(start + Duration::from_secs(timeout));
+    }
+    if let Some(max_expanded) = options.max_expanded {
+        limits = limits.max_expanded(max_expanded);
+    }
+
+    let mut stats = Stats::default();
+    let mut monitor = Monitor::<Move>::and(&mut stats, limits);
+    let mut result = options.algorithm.solve(taquin, options, heuristic, &mut monitor);
+    if options.shorten {
+        result = result.map(|moves| taquin.shorten_with_monitor(&moves, &mut monitor));
+    }
+    let elapsed = start.elapsed();
+
+    Solution { result, stats, elapsed }
+}
+
+/// Solve the taquins with `options.jobs` threads, solutions are reported in the input order
+fn solve_all<F>(taquins: &[Taquin], options: &SolveOptions, heuristic: &TaquinHeuristic, mut report: F)
+    where F: FnMut(usize, &Taquin, &Solution) {
+    if options.jobs <= 1 {
+        for (index, taquin) in taquins.iter().enumerate() {
+            report(index, taquin, &solve_with_limits(taquin, options, heuristic));
+        }
+        return;
+    }
+
+    let next_index = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _job in 0..options.jobs {
+            let sender = sender.clone();
+            let next_index = &next_index;
+            scope.spawn(move || loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                match taquins.get(index) {
+                    Some(taquin) => sender.send((index, solve_with_limits(taquin, options, heuristic)))
+                        .expect("Receiver should be alive"),
+                    None => break,
+                }
+            });
+        }
+        drop(sender);
+
+        // Report in order, keep early solutions until their turn
+        let mut pending = BTreeMap::new();
+        let mut current = 0;
+        for (index, solution) in receiver {
+            pending.insert(index, solution);
+            while let Some(solution) = pending.remove(&current) {
+                report(current, &taquins[current], &solution);
+                current += 1;
+            }
+        }
+    });
+}
+
+fn solve_taquins(taquins: Vec<Taquin>, options: &SolveOptions, heuristic: &TaquinHeuristic) {
+    if let Format::Csv = options.format {
+        println!("tiles,status,length,moves,expanded,max_frontier,elapsed_ms");
+    }
+    solve_all(&taquins, options, heuristic, |_index, taquin, solution| {
+        match options.format {
+            Format::Text => print_text(taquin, solution),
+            Format::Moves => println!("{}", solution.moves().map_or(String::from("-"), format_moves)),
+            Format::Json => println!("{}", to_json(taquin, solution)),
+            Format::Csv => println!("{}", to_csv(taquin, solution)),
+        }
+    });
+}
+
+fn print_text(taquin: &Taquin, solution: &Solution) {
     println!("Taquin to solve:\n{}", taquin);
-    let solve = taquin.solve();
-    let moves = solve.expect("Should be solvable");
-    println!("Found a solution in {} steps", moves.len());
-    print!("Moves: ");
-    for m in moves {
-        print!("{} ", m);
+    match &solution.result {
+        Ok(moves) => {
+            println!("Found a solution in {} steps", moves.len());
+            println!("Moves: {}", format_moves(moves));
+        }
+        Err(SolverError::Unsolvable) => println!("This taquin is not solvable"),
+        Err(SolverError::NoSolutionFound) => println!("No solution found"),
+        Err(SolverError::LimitReached { expanded, depth }) =>
+            println!("Limit reached after expanding {} states, at depth {}", expanded, depth),
+        Err(SolverError::Cancelled) => println!("Cancelled"),
     }
     println!();
 }
 
-// fn main() {
-//     let args = args().collect::<Vec<String>>();
-//     let Config { size, tiles } = parse_config(&args);
-//
-//     let taquin = Taquin::from_str(size, &tiles);
-//     solve_taquin(taquin);
-// }
-//
-// struct Config {
-//     size: u8,
-//     tiles: String,
-// }
-//
-// fn parse_config(args: &[String]) -> Config {
-//     if args.len() <= 1 {
-//         Config { size: 3, tiles: String::from("6,4,7,  8,5,0,  3,2,1") }
-//     } else if args.len() == 2 {
-//         let size = args[1].clone().parse::<u8>().unwrap_or(3);
-//         Config { size, tiles: String::from("6,4,7,  8,5,0,  3,2,1") }
-//     } else {
-//         let size = args[1].clone().parse::<u8>().unwrap_or(3);
-//         let tiles = args[2].clone();
-//         Config { size, tiles }
-//     }
-// }
+fn format_moves(moves: &[Move]) -> String {
+    moves.iter()
+        .map(Move::to_string)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn move_letters(moves: &[Move]) -> String {
+    moves.iter()
+        .map(|m| m.letter())
+        .collect()
+}
+
+fn to_json(taquin: &Taquin, solution: &Solution) -> String {
+    let tiles = taquin.tile_values().iter()
+        .map(TileValue::to_string)
+        .collect::<Vec<String>>()
+        .join(",");
+    let (moves, length) = match solution.moves() {
+        Some(moves) => (format!("\"{}\"", move_letters(moves)), moves.len().to_string()),
+        None => (String::from("null"), String::from("null")),
+    };
+    format!(
+        "{{\"rows\":{},\"columns\":{},\"tiles\":[{}],\"status\":\"{}\",\"length\":{},\"moves\":{},\"expanded\":{},\"max_frontier\":{},\"elapsed_ms\":{:.3}}}",
+        taquin.rows(), taquin.columns(), tiles, solution.status(), length, moves, solution.stats.expanded, solution.stats.max_frontier,
+        solution.elapsed.as_secs_f64() * 1000.0)
+}
+
+fn to_csv(taquin: &Taquin, solution: &Solution) -> String {
+    let (moves, length) = match solution.moves() {
+        Some(moves) => (move_letters(moves), moves.len().to_string()),
+        None => (String::new(), String::new()),
+    };
+    format!("\"{}\",{},{},{},{},{},{:.3}",
+            taquin.to_line(), solution.status(), length, moves, solution.stats.expanded, solution.stats.max_frontier,
+            solution.elapsed.as_secs_f64() * 1000.0)
+}
+
+/// Rows and columns of the `--size` option should be between `MIN_SIZE` and `MAX_SIZE`
+fn check_size(size: Dimensions) -> Result<(), Error> {
+    Taquin::check_dimensions(size).map_err(|error| Error::new(ErrorKind::InvalidInput, error))
+}
+
+/// Apply random moves to the goal, or draw uniformly among the solvable taquins without moves
+fn generate_taquins(size: Dimensions, count: u32, moves: Option<u32>, goal: &GoalOption, seed: Option<u64>) -> Result<(), Error> {
+    check_size(size)?;
+    let goal = goal.goal(size).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut rng = SplitMix64::new(seed);
+    println!("# seed: {}", seed);
+    if !size.is_square() {
+        // the size of a rectangle cannot be inferred from the number of tiles
+        println!("size: {}", size);
+    }
+    for _i in 0..count {
+        let taquin = match moves {
+            Some(moves) => {
+                let mut taquin = Taquin::solved(goal.clone());
+                taquin.shuffle_with_rng(moves, &mut rng);
+                taquin
+            }
+            None => Taquin::random_with_rng(goal.clone(), &mut rng),
+        };
+        println!("{}", taquin.to_line());
+    }
+    Ok(())
+}
+
+fn generate_exact_taquins(size: Dimensions, length: u32, count: usize, goal: &GoalOption, seed: Option<u64>,
+                          max_states: Option<usize>) -> Result<(), Error> {
+    check_size(size)?;
+    let goal = goal.goal(size).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut limits = Limits::new();
+    if let Some(max_states) = max_states {
+        limits = limits.max_visited(max_states);
+    }
+    let taquins = Taquin::at_distance_with_monitor(goal, length, count, &mut SplitMix64::new(seed), &mut limits)
+        .map_err(|error| Error::other(format!("no taquins at distance {}: {}", length, error)))?;
+
+    println!("# seed: {}", seed);
+    if !size.is_square() {
+        println!("size: {}", size);
+    }
+    for taquin in &taquins {
+        println!("{}", taquin.to_line());
+    }
+    if taquins.len() < count {
+        eprintln!("Only {} taquins at distance {}", taquins.len(), length);
+    }
+    Ok(())
+}
+
+fn count_distances(goal: &GoalOption) -> Result<(), Error> {
+    let goal = goal.goal(Dimensions::from(3)).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    let start = Instant::now();
+    let database = DistanceDatabase::build_for_goal(goal);
+    for (distance, count) in database.counts().iter().enumerate() {
+        println!("{}: {}", distance, count);
+    }
+    println!("Built in {:?}", start.elapsed());
+    Ok(())
+}
+
+fn build_pattern_database(size: Dimensions, output: &Path, goal: &GoalOption) -> Result<(), Error> {
+    check_size(size)?;
+    let groups = PatternDatabase::default_groups(size)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("no usual partition for size {}", size)))?;
+    let goal = goal.goal(size).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    let start = Instant::now();
+    let database = PatternDatabase::build_for_goal(goal, &groups)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    database.save(output)?;
+    println!("Built {:?} in {:?}", groups, start.elapsed());
+    Ok(())
+}
+
+fn check_taquins(taquins: Vec<Taquin>) {
+    for taquin in taquins {
+        let status = if taquin.is_solvable() { "solvable" } else { "unsolvable" };
+        println!("{}: {}", taquin.to_line(), status);
+    }
+}
+
+fn verify_moves(tiles: &str, moves: &str, goal: &GoalOption) -> Result<(), Error> {
+    let taquin = input::parse_taquin(tiles)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    let goal = goal.goal(taquin.dimensions()).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    let taquin = taquin.with_goal(goal);
+
+    let mut current = taquin;
+    let letters = moves.chars().filter(|c| c.is_alphabetic());
+    for (index, letter) in letters.enumerate() {
+        let next = Move::from_letter(letter)
+            .and_then(|m| current.try_move_hole(m));
+        current = next
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("invalid move #{}: '{}'", index, letter)))?;
+    }
+
+    if !current.is_solved() {
+        return Err(Error::new(ErrorKind::InvalidData, format!("not solved:\n{}", current)));
+    }
+    println!("Solved");
+    Ok(())
+}
+
+fn bench_taquins(taquins: Vec<Taquin>, options: &SolveOptions, heuristic: &TaquinHeuristic) {
+    let total = Instant::now();
+    let mut solved = 0;
+    solve_all(&taquins, options, heuristic, |index, _taquin, solution| {
+        match solution.moves() {
+            Some(moves) => {
+                solved += 1;
+                println!("#{}: {} moves, {} expanded in {:?}", index, moves.len(), solution.stats.expanded, solution.elapsed);
+            }
+            None => println!("#{}: {} after {:?}", index, solution.status(), solution.elapsed),
+        }
+    });
+    println!("Solved {}/{} taquins in {:?}", solved, taquins.len(), total.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod goal_option {
+        use super::*;
+
+        #[test]
+        fn rectangle() {
+            let goal = "2x4: 0,1,2,3,  4,5,6,7".parse::<GoalOption>().unwrap();
+            let dimensions = Dimensions::new(2, 4);
+            assert_eq!(goal.goal(dimensions), Ok(Goal::hole_first(dimensions)));
+            assert!(goal.goal(Dimensions::new(4, 2)).is_err());
+        }
+    }
+
+    mod verify_moves {
+        use super::*;
+
+        #[test]
+        fn rectangle() {
+            verify_moves("2x4: 1,2,3,4,  5,6,0,7", "L", &GoalOption::Sorted).unwrap();
+            let error = verify_moves("2x4: 1,2,3,4,  5,6,0,7", "R", &GoalOption::Sorted).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn rectangle_goal() {
+            let goal = "2x4: 0,1,2,3,  4,5,6,7".parse::<GoalOption>().unwrap();
+            verify_moves("2x4: 1,0,2,3,  4,5,6,7", "R", &goal).unwrap();
+        }
+    }
+}
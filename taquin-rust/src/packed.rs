@@ -0,0 +1,303 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use crate::goal::Goal;
+use crate::grid::Dimensions;
+use crate::solver::{Problem, ReversibleProblem};
+use crate::taquin::{Move, Taquin, TileValue};
+
+const BITS_PER_TILE: u8 = 4;
+const TILE_MASK: u64 = 0b1111;
+
+/// Most tiles that fit 4 bits per tile in a `u64`
+pub const MAX_PACKED_TILES: usize = 16;
+
+/// Compact taquin, up to 16 tiles, e.g. 4x4 or 3x5
+///
+/// Each tile takes 4 bits of a single `u64`, the tile at index `i` is stored at bits `4i..4i+4`,
+/// with `0` for the hole. The hole index is cached to avoid searching it.
+/// The goal tiles are packed the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedTaquin {
+    tiles: u64,
+    goal: u64,
+    hole: u8,
+    rows: u8,
+    columns: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeTooLarge(pub Dimensions);
+
+impl Display for SizeTooLarge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot pack a taquin of size {}, the maximum is {} tiles", self.0, MAX_PACKED_TILES)
+    }
+}
+
+impl Error for SizeTooLarge {}
+
+impl PackedTaquin {
+    pub fn new<D: Into<Dimensions>>(dimensions: D) -> Self {
+        PackedTaquin::try_from(&Taquin::new(dimensions))
+            .expect("Should have at most 16 tiles")
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions::new(self.rows, self.columns)
+    }
+
+    fn tile_count(&self) -> u8 {
+        self.rows * self.columns
+    }
+
+    pub(crate) fn get(&self, index: u8) -> u8 {
+        unpack(self.tiles, index)
+    }
+
+    pub fn target(&self) -> Goal {
+        let tiles: Vec<TileValue> = (0..self.tile_count())
+            .map(|index| TileValue::from(unpack(self.goal, index)))
+            .collect();
+        Goal::custom(self.dimensions(), &tiles)
+            .expect("Packed goal should be valid")
+    }
+
+    /// Goal index of each value
+    pub(crate) fn goal_positions(&self) -> [TileValue; 16] {
+        let mut positions = [0; 16];
+        for index in 0..self.tile_count() {
+            positions[usize::from(unpack(self.goal, index))] = TileValue::from(index);
+        }
+        positions
+    }
+
+    /// Tiles row by row, `0` stands for the hole
+    pub fn tile_values(&self) -> Vec<TileValue> {
+        (0..self.tile_count())
+            .map(|index| TileValue::from(self.get(index)))
+            .collect()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.tiles == self.goal
+    }
+
+    /// Sum of the distances of each tile to its goal position
+    pub fn manhattan_distance(&self) -> u32 {
+        let columns = self.columns;
+        let positions = self.goal_positions();
+        let mut distance = 0;
+        for index in 0..self.tile_count() {
+            let value = self.get(index);
+            if value != 0 {
+                let target = positions[usize::from(value)] as u8;
+                distance += u32::from((index / columns).abs_diff(target / columns))
+                    + u32::from((index % columns).abs_diff(target % columns));
+            }
+        }
+        distance
+    }
+
+    fn is_valid(&self, user_move: Move) -> bool {
+        let row = self.hole / self.columns;
+        let column = self.hole % self.columns;
+        match user_move {
+            Move::Up => row < self.rows - 1,
+            Move::Right => column > 0,
+            Move::Down => row > 0,
+            Move::Left => column < self.columns - 1,
+        }
+    }
+
+    /// Move the hole, an invalid move keeps the taquin unchanged
+    pub fn move_hole(&self, user_move: Move) -> Self {
+        if !self.is_valid(user_move) {
+            return *self;
+        }
+
+        let index = match user_move {
+            Move::Up => self.hole + self.columns,
+            Move::Right => self.hole - 1,
+            Move::Down => self.hole - self.columns,
+            Move::Left => self.hole + 1,
+        };
+        // The hole is 0, so moving the tile is enough
+        let value = u64::from(self.get(index));
+        let tiles = self.tiles
+            & !(TILE_MASK << (index * BITS_PER_TILE))
+            | (value << (self.hole * BITS_PER_TILE));
+
+        PackedTaquin { tiles, hole: index, ..*self }
+    }
+}
+
+impl TryFrom<&Taquin> for PackedTaquin {
+    type Error = SizeTooLarge;
+
+    fn try_from(taquin: &Taquin) -> Result<Self, Self::Error> {
+        let dimensions = taquin.dimensions();
+        if dimensions.cell_count() > MAX_PACKED_TILES { return Err(SizeTooLarge(dimensions)); }
+
+        let values = taquin.tile_values();
+        let hole = values.iter().position(|&value| value == 0)
+            .expect("No Hole found !") as u8;
+        let tiles = pack(&values);
+        let goal = pack(taquin.target().tiles());
+
+        Ok(PackedTaquin { tiles, goal, hole, rows: dimensions.rows(), columns: dimensions.columns() })
+    }
+}
+
+impl From<&PackedTaquin> for Taquin {
+    fn from(packed: &PackedTaquin) -> Self {
+        Taquin::from_values(packed.dimensions(), &packed.tile_values())
+            .with_goal(packed.target())
+    }
+}
+
+fn pack(values: &[TileValue]) -> u64 {
+    values.iter()
+        .enumerate()
+        .fold(0, |tiles, (index, &value)| tiles | u64::from(value) << (index as u8 * BITS_PER_TILE))
+}
+
+fn unpack(tiles: u64, index: u8) -> u8 {
+    ((tiles >> (index * BITS_PER_TILE)) & TILE_MASK) as u8
+}
+
+impl Display for PackedTaquin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Taquin::from(self))
+    }
+}
+
+/// Hash a single word, the tiles define the hole position, the goal is usually the same
+impl Hash for PackedTaquin {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.tiles)
+    }
+}
+
+// Solve
+impl Problem<Move> for PackedTaquin {
+    fn is_solved(&self) -> bool {
+        self.is_solved()
+    }
+
+    fn available_steps(&self, previous_steps: &[Move]) -> Vec<Move> {
+        let last_move = previous_steps.last();
+        Move::all().into_iter()
+            .filter(|&m| last_move != Some(&m.reverse()) && self.is_valid(m))
+            .collect()
+    }
+
+    fn next(&self, step: Move) -> Self {
+        self.move_hole(step)
+    }
+
+    fn is_solvable(&self) -> bool {
+        Taquin::from(self).is_solvable()
+    }
+}
+
+impl ReversibleProblem<Move> for PackedTaquin {
+    fn goal(&self) -> Self {
+        let hole = (0..self.tile_count())
+            .find(|&index| unpack(self.goal, index) == 0)
+            .expect("No Hole found !");
+        PackedTaquin { tiles: self.goal, hole, ..*self }
+    }
+
+    fn reverse_step(&self, step: &Move) -> Move {
+        step.reverse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod given {
+        use super::*;
+
+        pub fn a_taquin() -> Taquin {
+            Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6")
+        }
+    }
+
+    #[test]
+    fn size_of() {
+        assert_eq!(std::mem::size_of::<PackedTaquin>(), 24);
+    }
+
+    #[test]
+    fn round_trip() {
+        let taquin = given::a_taquin();
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        assert_eq!(Taquin::from(&packed), taquin);
+
+        let taquin = Taquin::from_str(4, "2,1,0,14,  13,9,4,8,  15,10,11,12,  7,5,6,3");
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        assert_eq!(Taquin::from(&packed), taquin);
+    }
+
+    #[test]
+    fn too_large() {
+        let taquin = Taquin::new(5);
+        assert_eq!(PackedTaquin::try_from(&taquin), Err(SizeTooLarge(Dimensions::from(5))));
+    }
+
+    #[test]
+    fn rectangle() {
+        let taquin = Taquin::from_str(Dimensions::new(3, 5), "1,2,3,4,5,  6,0,8,9,10,  11,7,12,13,14");
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        assert_eq!(Taquin::from(&packed), taquin);
+        assert_eq!(packed.manhattan_distance(), taquin.manhattan_distance());
+        for m in Move::all() {
+            assert_eq!(Taquin::from(&packed.move_hole(m)), taquin.move_hole(m), "Move {}", m);
+        }
+    }
+
+    #[test]
+    fn is_solved() {
+        assert!(PackedTaquin::new(3).is_solved());
+        assert!(PackedTaquin::new(4).is_solved());
+        assert!(!PackedTaquin::try_from(&given::a_taquin()).unwrap().is_solved());
+    }
+
+    #[test]
+    fn with_goal() {
+        let taquin = given::a_taquin().with_goal(Goal::spiral(3));
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        assert_eq!(Taquin::from(&packed), taquin);
+        assert_eq!(packed.manhattan_distance(), taquin.manhattan_distance());
+        assert!(packed.goal().is_solved());
+        assert_eq!(Taquin::from(&packed.goal()), taquin.goal());
+    }
+
+    #[test]
+    fn manhattan_distance() {
+        let packed = PackedTaquin::try_from(&given::a_taquin()).unwrap();
+        assert_eq!(packed.manhattan_distance(), given::a_taquin().manhattan_distance());
+    }
+
+    #[test]
+    fn move_hole() {
+        let taquin = given::a_taquin();
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        for m in Move::all() {
+            assert_eq!(Taquin::from(&packed.move_hole(m)), taquin.move_hole(m), "Move {}", m);
+        }
+    }
+
+    #[test]
+    fn available_steps() {
+        let taquin = given::a_taquin();
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        assert_eq!(packed.available_steps(&[]), taquin.available_steps(&[]));
+        assert_eq!(packed.available_steps(&[Move::Down]), taquin.available_steps(&[Move::Down]));
+    }
+}
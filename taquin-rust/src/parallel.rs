@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::sync::Mutex;
+
+use rayon::prelude::*;
+
+use crate::solver::{Monitor, Problem, SolverError, path_from_parents};
+
+const SHARDS: usize = 64;
+
+/// Visited set split in shards, each behind its own lock
+struct ShardedSet<P> {
+    shards: Vec<Mutex<HashSet<P>>>,
+    hasher: RandomState,
+}
+
+impl<P> ShardedSet<P> where P: Hash + Eq {
+    fn new(shards: usize) -> Self {
+        let shards = (0..shards)
+            .map(|_| Mutex::new(HashSet::new()))
+            .collect();
+        ShardedSet { shards, hasher: RandomState::new() }
+    }
+
+    /// `true` if the state was not already there
+    fn insert(&self, state: P) -> bool {
+        let shard = self.hasher.hash_one(&state) as usize % self.shards.len();
+        self.shards[shard].lock()
+            .expect("Poisoned shard")
+            .insert(state)
+    }
+
+    fn len(&self) -> usize {
+        self.shards.iter()
+            .map(|shard| shard.lock().expect("Poisoned shard").len())
+            .sum()
+    }
+}
+
+/// Level-synchronous breadth-first search
+///
+/// Each level is expanded in parallel, the monitor is called between levels.
+pub(crate) fn bfs<P, S, M>(initial: &P, monitor: &mut M) -> Result<Vec<S>, SolverError>
+    where P: Problem<S> + Send + Sync, S: Clone + Send + Sync, M: Monitor<S> {
+    let visited = ShardedSet::new(SHARDS);
+    visited.insert(initial.clone());
+    let mut parents: Vec<Option<(usize, S)>> = vec![None];
+    let mut frontier = vec![(initial.clone(), 0)];
+    let mut depth = 0;
+
+    while !frontier.is_empty() {
+        monitor.next_depth(depth);
+
+        // Try found solution
+        if let Some((_, index)) = frontier.par_iter().find_first(|(state, _)| state.is_solved()) {
+            let steps = path_from_parents(&parents, *index);
+            monitor.found(&steps);
+            return Ok(steps);
+        }
+
+        monitor.check()?;
+
+        let expansions: Vec<Vec<(P, usize, S)>> = frontier.par_iter()
+            .map(|(state, index)| {
+                let previous = match &parents[*index] {
+                    Some((_, step)) => std::slice::from_ref(step),
+                    None => &[],
+                };
+                state.available_steps(previous).into_iter()
+                    .map(|step| (state.next(step.clone()), *index, step))
+                    .filter(|(new_state, _, _)| visited.insert(new_state.clone()))
+                    .collect()
+            })
+            .collect();
+
+        let mut next = vec![];
+        for new_states in expansions {
+            let count = new_states.len();
+            for (new_state, parent, step) in new_states {
+                next.push((new_state, parents.len()));
+                parents.push(Some((parent, step)));
+            }
+            monitor.expanded(count, next.len());
+        }
+        monitor.visited_states(visited.len());
+
+        frontier = next;
+        depth += 1;
+    }
+
+    Err(SolverError::NoSolutionFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sharded_set() {
+        let set = ShardedSet::new(4);
+        assert!(set.insert(1));
+        assert!(set.insert(2));
+        assert!(!set.insert(1));
+        assert_eq!(set.len(), 2);
+    }
+}
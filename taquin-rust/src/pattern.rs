@@ -0,0 +1,509 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+use crate::goal::Goal;
+use crate::grid::{Dimensions, Size};
+use crate::packed::PackedTaquin;
+use crate::solver::Heuristic;
+use crate::taquin::{Taquin, TileValue};
+
+/// Cells are tracked with a `u64` bit set while building, so rows and columns are limited
+pub const MAX_PATTERN_SIZE: Size = 8;
+
+const MAGIC: &[u8; 6] = b"TQPDB3";
+const UNKNOWN: u8 = u8::MAX;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    InvalidSize(Dimensions),
+    EmptyGroup(usize),
+    InvalidTile(u8),
+    DuplicateTile(u8),
+    TooLarge(usize),
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::InvalidSize(dimensions) =>
+                write!(f, "rows and columns should be between 2 and {}, got {}", MAX_PATTERN_SIZE, dimensions),
+            PatternError::EmptyGroup(index) =>
+                write!(f, "group #{} is empty", index),
+            PatternError::InvalidTile(tile) =>
+                write!(f, "tile {} is out of range", tile),
+            PatternError::DuplicateTile(tile) =>
+                write!(f, "tile {} is in several groups", tile),
+            PatternError::TooLarge(index) =>
+                write!(f, "group #{} has too many placements", index),
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+/// Fewest moves of the group tiles to bring them home, for each placement of the group
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    tiles: Vec<u8>,
+    distances: Vec<u8>,
+}
+
+/// Additive disjoint pattern databases
+///
+/// The tiles are split into disjoint groups. For each group, a backward BFS from the goal
+/// records the fewest moves of the group tiles needed to solve any placement of the group,
+/// the other tiles being indistinguishable. Only the moves of the group tiles are counted,
+/// so the estimates of the groups add up to an admissible heuristic, at least as good as
+/// the Manhattan distance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternDatabase {
+    goal: Goal,
+    patterns: Vec<Pattern>,
+}
+
+impl PatternDatabase {
+    /// Build the databases of each group with a BFS
+    ///
+    /// The time and memory grow with the number of placements of the largest group:
+    /// a 6 tiles group of a 4x4 taquin has 5,765,760 placements, but a 6 tiles group
+    /// of a 5x5 taquin has 127,512,000 placements.
+    pub fn build<D: Into<Dimensions>>(dimensions: D, groups: &[Vec<u8>]) -> Result<Self, PatternError> {
+        let dimensions = dimensions.into();
+        validate(dimensions, groups)?;
+        PatternDatabase::build_for_goal(Goal::sorted(dimensions), groups)
+    }
+
+    pub fn build_for_goal(goal: Goal, groups: &[Vec<u8>]) -> Result<Self, PatternError> {
+        validate(goal.dimensions(), groups)?;
+        let patterns = groups.iter()
+            .map(|tiles| Pattern {
+                tiles: tiles.clone(),
+                distances: build_distances(&goal, tiles),
+            })
+            .collect();
+
+        Ok(PatternDatabase { goal, patterns })
+    }
+
+    /// Usual partitions: 4-4 for 3x3, 6-6-3 for 4x4, 6-6-6-6 for 5x5,
+    /// groups of 5 consecutive tiles for other rectangles
+    pub fn default_groups<D: Into<Dimensions>>(dimensions: D) -> Option<Vec<Vec<u8>>> {
+        let dimensions = dimensions.into();
+        if !dimensions.is_square() {
+            let tiles: Vec<u8> = (1..dimensions.cell_count()).map(|tile| tile as u8).collect();
+            return Some(tiles.chunks(5).map(|group| group.to_vec()).collect());
+        }
+        match dimensions.rows() {
+            3 => Some(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]),
+            4 => Some(vec![
+                vec![1, 5, 6, 9, 10, 13],
+                vec![7, 8, 11, 12, 14, 15],
+                vec![2, 3, 4],
+            ]),
+            5 => Some(vec![
+                vec![1, 2, 5, 6, 7, 12],
+                vec![3, 4, 8, 9, 13, 14],
+                vec![10, 11, 15, 16, 20, 21],
+                vec![17, 18, 19, 22, 23, 24],
+            ]),
+            _ => None,
+        }
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.goal.dimensions()
+    }
+
+    pub fn goal(&self) -> &Goal {
+        &self.goal
+    }
+
+    pub fn groups(&self) -> Vec<Vec<u8>> {
+        self.patterns.iter()
+            .map(|pattern| pattern.tiles.clone())
+            .collect()
+    }
+
+    /// Sum of the group distances, the values are the tiles row by row with `0` for the hole
+    fn estimate_values<I>(&self, values: I) -> u32 where I: Iterator<Item=TileValue> {
+        let mut positions = vec![0; self.goal.tiles().len()];
+        for (index, value) in values.enumerate() {
+            positions[usize::from(value)] = index as u8;
+        }
+
+        let cells = positions.len();
+        self.patterns.iter()
+            .map(|pattern| {
+                let placement: Vec<u8> = pattern.tiles.iter()
+                    .map(|&tile| positions[usize::from(tile)])
+                    .collect();
+                u32::from(pattern.distances[rank(cells, &placement)])
+            })
+            .sum()
+    }
+
+    /// Binary format: magic, rows, columns, goal tiles, group count, then each group length and tiles,
+    /// then the distances of each group, one byte per placement
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[self.dimensions().rows(), self.dimensions().columns()])?;
+        // at most 64 cells, a tile fits in a byte
+        let goal: Vec<u8> = self.goal.tiles().iter().map(|&tile| tile as u8).collect();
+        writer.write_all(&goal)?;
+        writer.write_all(&[self.patterns.len() as u8])?;
+        for pattern in self.patterns.iter() {
+            writer.write_all(&[pattern.tiles.len() as u8])?;
+            writer.write_all(&pattern.tiles)?;
+        }
+        for pattern in self.patterns.iter() {
+            writer.write_all(&pattern.distances)?;
+        }
+        writer.flush()
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0; 6];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a pattern database"));
+        }
+
+        let mut dimensions = [0; 2];
+        reader.read_exact(&mut dimensions)?;
+        let dimensions = Dimensions::new(dimensions[0], dimensions[1]);
+        if !is_valid(dimensions) {
+            return Err(invalid_data(&PatternError::InvalidSize(dimensions).to_string()));
+        }
+        let mut goal = vec![0; dimensions.cell_count()];
+        reader.read_exact(&mut goal)?;
+        let goal: Vec<TileValue> = goal.into_iter().map(TileValue::from).collect();
+        let goal = Goal::custom(dimensions, &goal)
+            .map_err(|error| invalid_data(&error.to_string()))?;
+
+        let mut group_count = [0];
+        reader.read_exact(&mut group_count)?;
+        let mut groups = vec![];
+        for _ in 0..group_count[0] {
+            let mut length = [0];
+            reader.read_exact(&mut length)?;
+            let mut tiles = vec![0; usize::from(length[0])];
+            reader.read_exact(&mut tiles)?;
+            groups.push(tiles);
+        }
+        validate(dimensions, &groups).map_err(|error| invalid_data(&error.to_string()))?;
+
+        let cells = dimensions.cell_count();
+        let mut patterns = vec![];
+        for tiles in groups {
+            let count = placement_count(cells, tiles.len()).expect("Validated groups should fit");
+            let mut distances = vec![0; count];
+            reader.read_exact(&mut distances)?;
+            patterns.push(Pattern { tiles, distances });
+        }
+
+        Ok(PatternDatabase { goal, patterns })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        PatternDatabase::read(BufReader::new(File::open(path)?))
+    }
+}
+
+/// The taquin should have the goal of the database
+impl Heuristic<Taquin> for PatternDatabase {
+    fn estimate(&self, taquin: &Taquin) -> u32 {
+        debug_assert_eq!(taquin.target(), &self.goal);
+        self.estimate_values(taquin.tile_values().into_iter())
+    }
+}
+
+/// The taquin should have the goal of the database
+impl Heuristic<PackedTaquin> for PatternDatabase {
+    fn estimate(&self, taquin: &PackedTaquin) -> u32 {
+        debug_assert_eq!(taquin.target(), self.goal);
+        let cells = taquin.dimensions().cell_count() as u8;
+        self.estimate_values((0..cells).map(|index| TileValue::from(taquin.get(index))))
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn is_valid(dimensions: Dimensions) -> bool {
+    (2..=MAX_PATTERN_SIZE).contains(&dimensions.rows()) && (2..=MAX_PATTERN_SIZE).contains(&dimensions.columns())
+}
+
+fn validate(dimensions: Dimensions, groups: &[Vec<u8>]) -> Result<(), PatternError> {
+    if !is_valid(dimensions) {
+        return Err(PatternError::InvalidSize(dimensions));
+    }
+
+    let cells = dimensions.cell_count();
+    let mut seen = vec![false; cells];
+    for (index, tiles) in groups.iter().enumerate() {
+        if tiles.is_empty() {
+            return Err(PatternError::EmptyGroup(index));
+        }
+        for &tile in tiles.iter() {
+            let tile_index = usize::from(tile);
+            if tile_index == 0 || tile_index >= cells {
+                return Err(PatternError::InvalidTile(tile));
+            }
+            if seen[tile_index] {
+                return Err(PatternError::DuplicateTile(tile));
+            }
+            seen[tile_index] = true;
+        }
+        let bits = placement_count(cells, tiles.len())
+            .and_then(|count| (count as u64).checked_mul(cells as u64));
+        if bits.is_none_or(|bits| bits > u64::from(u32::MAX)) {
+            return Err(PatternError::TooLarge(index));
+        }
+    }
+    Ok(())
+}
+
+/// Number of ways to put `tiles` distinct tiles on `cells` cells, `None` on overflow
+fn placement_count(cells: usize, tiles: usize) -> Option<usize> {
+    (cells - tiles + 1..=cells).try_fold(1_usize, |count, cell| count.checked_mul(cell))
+}
+
+/// Index of a placement, a mixed radix number where each position
+/// skips the cells already taken by the previous tiles
+fn rank(cells: usize, placement: &[u8]) -> usize {
+    let mut result = 0;
+    for (i, &position) in placement.iter().enumerate() {
+        let smaller_before = placement[..i].iter().filter(|&&other| other < position).count();
+        result = result * (cells - i) + usize::from(position) - smaller_before;
+    }
+    result
+}
+
+fn unrank(cells: usize, tiles: usize, mut index: usize, placement: &mut Vec<u8>) {
+    placement.clear();
+    let mut digits = vec![0; tiles];
+    for i in (0..tiles).rev() {
+        let radix = cells - i;
+        digits[i] = index % radix;
+        index /= radix;
+    }
+
+    let mut taken = 0_u64;
+    for digit in digits {
+        let position = (0..cells)
+            .filter(|&cell| taken & (1 << cell) == 0)
+            .nth(digit)
+            .expect("Digit should be smaller than the radix");
+        taken |= 1 << position;
+        placement.push(position as u8);
+    }
+}
+
+/// Level by level BFS from the goal placement
+///
+/// A state is a placement of the group tiles with the hole. The hole moves freely
+/// between the cells without a group tile, so all the cells of its region are visited at once,
+/// and only moving a group tile into the region costs a move.
+fn build_distances(goal: &Goal, tiles: &[u8]) -> Vec<u8> {
+    let rows = usize::from(goal.dimensions().rows());
+    let columns = usize::from(goal.dimensions().columns());
+    let cells = rows * columns;
+    let count = placement_count(cells, tiles.len()).expect("Validated groups should fit");
+    let mut distances = vec![UNKNOWN; count];
+    let mut visited = vec![0_u64; (count * cells).div_ceil(64)];
+    let is_visited = |visited: &[u64], state: usize| visited[state / 64] & (1 << (state % 64)) != 0;
+
+    let neighbors: Vec<Vec<usize>> = (0..cells)
+        .map(|cell| {
+            let (row, column) = (cell / columns, cell % columns);
+            let mut result = vec![];
+            if row > 0 { result.push(cell - columns); }
+            if row + 1 < rows { result.push(cell + columns); }
+            if column > 0 { result.push(cell - 1); }
+            if column + 1 < columns { result.push(cell + 1); }
+            result
+        })
+        .collect();
+
+    let goal_placement: Vec<u8> = tiles.iter().map(|&tile| goal.position(TileValue::from(tile)) as u8).collect();
+    let mut frontier = vec![rank(cells, &goal_placement) * cells + goal.position(0)];
+    let mut placement = Vec::with_capacity(tiles.len());
+    let mut stack = vec![];
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        let mut next_frontier = vec![];
+        for state in frontier {
+            if is_visited(&visited, state) { continue; }
+            let (index, hole) = (state / cells, state % cells);
+            if distances[index] == UNKNOWN {
+                distances[index] = depth;
+            }
+
+            unrank(cells, tiles.len(), index, &mut placement);
+            let occupied = placement.iter().fold(0_u64, |mask, &position| mask | (1 << position));
+            stack.push(hole);
+            while let Some(cell) = stack.pop() {
+                let cell_state = index * cells + cell;
+                if is_visited(&visited, cell_state) { continue; }
+                visited[cell_state / 64] |= 1 << (cell_state % 64);
+
+                for &neighbor in neighbors[cell].iter() {
+                    if occupied & (1 << neighbor) == 0 {
+                        stack.push(neighbor);
+                        continue;
+                    }
+                    // the group tile slides into the hole
+                    let tile_index = placement.iter().position(|&position| usize::from(position) == neighbor)
+                        .expect("Occupied cell should have a tile");
+                    placement[tile_index] = cell as u8;
+                    let next_state = rank(cells, &placement) * cells + neighbor;
+                    placement[tile_index] = neighbor as u8;
+                    if !is_visited(&visited, next_state) {
+                        next_frontier.push(next_state);
+                    }
+                }
+            }
+        }
+        frontier = next_frontier;
+        depth += 1;
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    mod given {
+        use super::*;
+
+        pub fn a_taquin() -> Taquin {
+            Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6")
+        }
+
+        pub fn a_database() -> PatternDatabase {
+            PatternDatabase::build(3, &PatternDatabase::default_groups(3).unwrap()).unwrap()
+        }
+    }
+
+    #[test]
+    fn rank_round_trip() {
+        let mut placement = vec![];
+        for index in 0..placement_count(9, 3).unwrap() {
+            unrank(9, 3, index, &mut placement);
+            assert_eq!(rank(9, &placement), index);
+        }
+    }
+
+    mod build {
+        use super::*;
+
+        #[test]
+        fn ok() {
+            let database = given::a_database();
+            assert_eq!(database.estimate(&Taquin::new(3)), 0);
+            // the taquin is solved in 17 moves
+            assert!(database.estimate(&given::a_taquin()) <= 17);
+        }
+
+        #[test]
+        fn with_goal() {
+            let goal = Goal::spiral(3);
+            let database = PatternDatabase::build_for_goal(goal.clone(), &PatternDatabase::default_groups(3).unwrap()).unwrap();
+            assert_eq!(database.estimate(&Taquin::solved(goal.clone())), 0);
+
+            let taquin = given::a_taquin().with_goal(goal);
+            assert!(database.estimate(&taquin) >= taquin.manhattan_distance());
+        }
+
+        #[test]
+        fn invalid_groups() {
+            assert_eq!(PatternDatabase::build(3, &[vec![1, 2], vec![]]), Err(PatternError::EmptyGroup(1)));
+            assert_eq!(PatternDatabase::build(3, &[vec![1, 9]]), Err(PatternError::InvalidTile(9)));
+            assert_eq!(PatternDatabase::build(3, &[vec![0]]), Err(PatternError::InvalidTile(0)));
+            assert_eq!(PatternDatabase::build(3, &[vec![1, 2], vec![2]]), Err(PatternError::DuplicateTile(2)));
+            assert_eq!(PatternDatabase::build(9, &[vec![1]]), Err(PatternError::InvalidSize(Dimensions::from(9))));
+            assert_eq!(PatternDatabase::build(Dimensions::new(2, 9), &[vec![1]]), Err(PatternError::InvalidSize(Dimensions::new(2, 9))));
+        }
+
+        #[test]
+        fn too_large() {
+            let group: Vec<u8> = (1..=30).collect();
+            assert_eq!(PatternDatabase::build(8, &[group]), Err(PatternError::TooLarge(0)));
+        }
+    }
+
+    mod estimate {
+        use super::*;
+
+        #[test]
+        fn at_least_manhattan() {
+            let database = given::a_database();
+            let taquin = given::a_taquin();
+            assert!(database.estimate(&taquin) >= taquin.manhattan_distance());
+        }
+
+        #[test]
+        fn packed() {
+            let database = given::a_database();
+            let taquin = given::a_taquin();
+            let packed = PackedTaquin::try_from(&taquin).unwrap();
+            assert_eq!(database.estimate(&packed), database.estimate(&taquin));
+        }
+    }
+
+    mod serialization {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let database = given::a_database();
+            let mut bytes = vec![];
+            database.write(&mut bytes).unwrap();
+            assert_eq!(bytes.len(), 6 + 2 + 9 + 1 + 2 * 5 + 2 * 3024);
+            assert_eq!(PatternDatabase::read(bytes.as_slice()).unwrap(), database);
+        }
+
+        #[test]
+        fn invalid() {
+            let error = PatternDatabase::read(&b"TQPDB1"[..]).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+            let error = PatternDatabase::read(&b"TQPDB2\x02\x01\x02\x03\x00\x01\x01\x01\x00"[..]).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+            let error = PatternDatabase::read(&b"TQPDB3\x02\x09"[..]).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+            let error = PatternDatabase::read(&b"TQPDB3\x02\x02\x01\x01\x02\x03"[..]).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+            let error = PatternDatabase::read(&b"TQPDB3\x02\x02\x01\x02\x03\x00\x01\x01\x04"[..]).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+            let error = PatternDatabase::read(&b"TQPDB3\x02\x02\x01\x02\x03\x00\x01\x01\x01\x00"[..]).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        }
+
+        #[test]
+        fn too_large() {
+            // 8x8 goal with a group of 30 tiles, the number of placements overflows
+            let mut bytes = b"TQPDB3\x08\x08".to_vec();
+            bytes.extend(0..64);
+            bytes.extend(&[1, 30]);
+            bytes.extend(1..=30);
+            let error = PatternDatabase::read(bytes.as_slice()).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::taquin::TileValue;
+
+/// `20!` is the largest factorial held by a `u64`
+pub const MAX_RANKED_VALUES: usize = 20;
+
+/// Lexicographic rank of a permutation of `0` to `n-1`, with its Lehmer code
+///
+/// Each digit of the code is the number of smaller values after the current one,
+/// the digits are read in the factorial number system.
+///
+/// # Panics
+///
+/// Panics if there are more than `MAX_RANKED_VALUES` values, or if they are not a permutation.
+pub fn rank<I>(values: I) -> u64
+    where I: IntoIterator<Item = TileValue>, I::IntoIter: ExactSizeIterator {
+    let values = values.into_iter();
+    let count = values.len();
+    if count > MAX_RANKED_VALUES {
+        panic!("At most {} values can be ranked, got {}", MAX_RANKED_VALUES, count);
+    }
+
+    let mut used = 0_u32;
+    let mut rank = 0;
+    for (index, value) in values.enumerate() {
+        let value = u32::from(value);
+        if value as usize >= count || used & 1 << value != 0 {
+            panic!("Expected a permutation of 0 to {}, got {}", count - 1, value);
+        }
+        let bit = 1 << value;
+        let smaller_used = (used & (bit - 1)).count_ones();
+        used |= bit;
+        rank = rank * (count - index) as u64 + u64::from(value - smaller_used);
+    }
+    rank
+}
+
+/// Permutation of `0` to `count-1` with this lexicographic rank, `None` if the rank is too large
+pub fn unrank(count: usize, rank: u64) -> Option<Vec<TileValue>> {
+    if count > MAX_RANKED_VALUES || rank >= factorial(count) {
+        return None;
+    }
+
+    // Lehmer code, from the last digit
+    let mut digits = vec![0; count];
+    let mut rest = rank;
+    for (index, digit) in digits.iter_mut().enumerate().rev() {
+        let base = (count - index) as u64;
+        *digit = (rest % base) as usize;
+        rest /= base;
+    }
+
+    let mut remaining: Vec<TileValue> = (0..count).map(|value| value as TileValue).collect();
+    Some(digits.into_iter()
+        .map(|digit| remaining.remove(digit))
+        .collect())
+}
+
+/// Number of permutations of `count` values
+pub fn factorial(count: usize) -> u64 {
+    (1..=count as u64).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod rank {
+        use super::*;
+
+        #[test]
+        fn first_and_last() {
+            assert_eq!(rank(vec![0, 1, 2, 3]), 0);
+            assert_eq!(rank(vec![3, 2, 1, 0]), 23);
+            assert_eq!(rank(vec![]), 0);
+        }
+
+        #[test]
+        fn lexicographic() {
+            assert_eq!(rank(vec![0, 2, 1]), 1);
+            assert_eq!(rank(vec![1, 0, 2]), 2);
+            assert_eq!(rank(vec![2, 0, 1]), 4);
+        }
+
+        #[test]
+        fn largest() {
+            let values: Vec<TileValue> = (0..20).rev().collect();
+            assert_eq!(rank(values), factorial(20) - 1);
+        }
+
+        #[test]
+        #[should_panic(expected = "Expected a permutation of 0 to 2, got 1")]
+        fn duplicate() {
+            rank(vec![1, 1, 0]);
+        }
+
+        #[test]
+        #[should_panic(expected = "At most 20 values can be ranked, got 21")]
+        fn too_many() {
+            rank((0..21).collect::<Vec<TileValue>>());
+        }
+    }
+
+    mod unrank {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            for value in 0..factorial(5) {
+                let permutation = unrank(5, value).expect("Rank should be valid");
+                assert_eq!(rank(permutation), value);
+            }
+        }
+
+        #[test]
+        fn ok() {
+            assert_eq!(unrank(3, 4), Some(vec![2, 0, 1]));
+        }
+
+        #[test]
+        fn out_of_range() {
+            assert_eq!(unrank(3, 6), None);
+            assert_eq!(unrank(21, 0), None);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use rand::{Error, RngCore, SeedableRng};
+
+/// SplitMix64 generator, small enough to be written again in the wasm crate
+///
+/// A seed gives the same numbers on every platform and with every version of `rand`,
+/// so a shuffled taquin is reproduced from its seed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+}
+
+/// Index lower than `len`, picked the same way in the wasm crate
+pub fn index<R: RngCore + ?Sized>(rng: &mut R, len: usize) -> usize {
+    (rng.next_u64() % len as u64) as usize
+}
+
+/// Up to `count` items drawn without replacement, with a partial Fisher-Yates shuffle
+pub fn sample<T, R: RngCore + ?Sized>(mut items: Vec<T>, count: usize, rng: &mut R) -> Vec<T> {
+    let count = count.min(items.len());
+    for current in 0..count {
+        let other = current + index(rng, items.len() - current);
+        items.swap(current, other);
+    }
+    items.truncate(count);
+    items
+}
+
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for SplitMix64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        SplitMix64::new(u64::from_le_bytes(seed))
+    }
+
+    /// The seed is the state, unlike the default that scrambles it first
+    fn seed_from_u64(state: u64) -> Self {
+        SplitMix64::new(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod next_u64 {
+        use super::*;
+
+        #[test]
+        fn reference() {
+            // first outputs of the reference implementation seeded with 1234567
+            let mut rng = SplitMix64::new(1_234_567);
+            assert_eq!(rng.next_u64(), 6_457_827_717_110_365_317);
+            assert_eq!(rng.next_u64(), 3_203_168_211_198_807_973);
+        }
+
+        #[test]
+        fn same_seed() {
+            let mut first = SplitMix64::seed_from_u64(42);
+            let mut second = SplitMix64::from_seed(42u64.to_le_bytes());
+            for _i in 0..10 {
+                assert_eq!(first.next_u64(), second.next_u64());
+            }
+        }
+    }
+
+    mod index {
+        use super::*;
+
+        #[test]
+        fn all_indexes() {
+            let mut rng = SplitMix64::new(0);
+            let mut seen = [false; 3];
+            for _i in 0..100 {
+                seen[index(&mut rng, 3)] = true;
+            }
+            assert_eq!(seen, [true; 3]);
+        }
+    }
+
+    mod sample {
+        use super::*;
+
+        #[test]
+        fn distinct() {
+            let mut rng = SplitMix64::new(0);
+            let mut items = sample((0..10).collect(), 4, &mut rng);
+            assert_eq!(items.len(), 4);
+            items.sort_unstable();
+            items.dedup();
+            assert_eq!(items.len(), 4);
+        }
+
+        #[test]
+        fn fewer_items() {
+            let mut rng = SplitMix64::new(0);
+            let mut items = sample(vec![1, 2, 3], 10, &mut rng);
+            items.sort_unstable();
+            assert_eq!(items, vec![1, 2, 3]);
+        }
+    }
+}
@@ -1,10 +1,242 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
-use crate::solver::SolverError::NoSolutionFound;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use crate::solver::SolverError::{Cancelled, LimitReached, NoSolutionFound, Unsolvable};
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum SolverError {
-    NoSolutionFound
+    NoSolutionFound,
+    Unsolvable,
+    LimitReached { expanded: u64, depth: u32 },
+    Cancelled,
+}
+
+impl Display for SolverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NoSolutionFound => write!(f, "no solution found"),
+            Unsolvable => write!(f, "unsolvable"),
+            LimitReached { expanded, depth } =>
+                write!(f, "limit reached after expanding {} states, at depth {}", expanded, depth),
+            Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl Error for SolverError {}
+
+/// Estimate of the remaining number of steps to reach a solution
+///
+/// To keep the A* solution optimal, the estimate should never
+/// overestimate the real distance (admissible heuristic).
+pub trait Heuristic<P> {
+    fn estimate(&self, problem: &P) -> u32;
+}
+
+impl<P, F> Heuristic<P> for F where F: Fn(&P) -> u32 {
+    fn estimate(&self, problem: &P) -> u32 {
+        self(problem)
+    }
+}
+
+/// Follow the progress of a search
+///
+/// The depth is the BFS level, or the f-cost bound for A* and IDA*.
+/// The frontier is the number of states waiting to be expanded,
+/// for IDA* it's the length of the current path.
+pub trait Monitor<S> {
+    fn next_depth(&mut self, _depth: u32) {}
+
+    fn expanded(&mut self, _new_states: usize, _frontier: usize) {}
+
+    /// Size of the visited set, IDA* does not keep one
+    fn visited_states(&mut self, _count: usize) {}
+
+    fn found(&mut self, _steps: &[S]) {}
+
+    /// Called before each expansion, an error stops the search
+    fn check(&mut self) -> Result<(), SolverError> {
+        Ok(())
+    }
+
+    fn and<M>(self, other: M) -> And<Self, M> where Self: Sized, M: Monitor<S> {
+        And(self, other)
+    }
+}
+
+/// No monitoring
+impl<S> Monitor<S> for () {}
+
+impl<S, M> Monitor<S> for &mut M where M: Monitor<S> {
+    fn next_depth(&mut self, depth: u32) {
+        (**self).next_depth(depth)
+    }
+
+    fn expanded(&mut self, new_states: usize, frontier: usize) {
+        (**self).expanded(new_states, frontier)
+    }
+
+    fn visited_states(&mut self, count: usize) {
+        (**self).visited_states(count)
+    }
+
+    fn found(&mut self, steps: &[S]) {
+        (**self).found(steps)
+    }
+
+    fn check(&mut self) -> Result<(), SolverError> {
+        (**self).check()
+    }
+}
+
+/// Both monitors, see `Monitor::and`
+pub struct And<A, B>(A, B);
+
+impl<S, A, B> Monitor<S> for And<A, B> where A: Monitor<S>, B: Monitor<S> {
+    fn next_depth(&mut self, depth: u32) {
+        self.0.next_depth(depth);
+        self.1.next_depth(depth);
+    }
+
+    fn expanded(&mut self, new_states: usize, frontier: usize) {
+        self.0.expanded(new_states, frontier);
+        self.1.expanded(new_states, frontier);
+    }
+
+    fn visited_states(&mut self, count: usize) {
+        self.0.visited_states(count);
+        self.1.visited_states(count);
+    }
+
+    fn found(&mut self, steps: &[S]) {
+        self.0.found(steps);
+        self.1.found(steps);
+    }
+
+    fn check(&mut self) -> Result<(), SolverError> {
+        self.0.check()?;
+        self.1.check()
+    }
+}
+
+/// Collect search statistics
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub expanded: u64,
+    pub max_frontier: usize,
+    pub depth: u32,
+}
+
+impl<S> Monitor<S> for Stats {
+    fn next_depth(&mut self, depth: u32) {
+        self.depth = depth;
+    }
+
+    fn expanded(&mut self, _new_states: usize, frontier: usize) {
+        self.expanded += 1;
+        self.max_frontier = self.max_frontier.max(frontier);
+    }
+}
+
+/// Shared flag to stop a search from another thread
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Stop the search when a limit is reached, or when cancelled
+///
+/// To be used as a monitor, e.g. `problem.solve_with_monitor(&mut limits)`,
+/// the visited set memory is bounded by its number of states.
+#[derive(Debug, Default, Clone)]
+pub struct Limits {
+    pub max_expanded: Option<u64>,
+    pub max_depth: Option<u32>,
+    pub max_visited: Option<usize>,
+    pub deadline: Option<Instant>,
+    pub cancellation: Option<CancellationToken>,
+    expanded: u64,
+    depth: u32,
+    visited: usize,
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Limits::default()
+    }
+
+    pub fn max_expanded(mut self, max_expanded: u64) -> Self {
+        self.max_expanded = Some(max_expanded);
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn max_visited(mut self, max_visited: usize) -> Self {
+        self.max_visited = Some(max_visited);
+        self
+    }
+
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    fn is_reached(&self) -> bool {
+        self.max_expanded.is_some_and(|max| self.expanded >= max)
+            || self.max_depth.is_some_and(|max| self.depth > max)
+            || self.max_visited.is_some_and(|max| self.visited > max)
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+impl<S> Monitor<S> for Limits {
+    fn next_depth(&mut self, depth: u32) {
+        self.depth = depth;
+    }
+
+    fn expanded(&mut self, _new_states: usize, _frontier: usize) {
+        self.expanded += 1;
+    }
+
+    fn visited_states(&mut self, count: usize) {
+        self.visited = count;
+    }
+
+    fn check(&mut self) -> Result<(), SolverError> {
+        if self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            Err(Cancelled)
+        } else if self.is_reached() {
+            Err(LimitReached { expanded: self.expanded, depth: self.depth })
+        } else {
+            Ok(())
+        }
+    }
 }
 
 pub trait Problem<S: Clone>: Hash + Clone + Eq {
@@ -14,43 +246,504 @@ pub trait Problem<S: Clone>: Hash + Clone + Eq {
 
     fn next(&self, step: S) -> Self;
 
+    /// Cheap check to avoid exploring the whole state space when no solution exists
+    fn is_solvable(&self) -> bool {
+        true
+    }
+
     fn solve(&self) -> Result<Vec<S>, SolverError> {
-        let mut states = HashSet::new();
-        states.insert(self.clone());
-        let initial = vec![(self.clone(), vec![])];
+        self.solve_with_monitor(&mut ())
+    }
+
+    fn solve_with_monitor<M>(&self, monitor: &mut M) -> Result<Vec<S>, SolverError>
+        where M: Monitor<S> {
+        if !self.is_solvable() { return Err(Unsolvable); }
+
+        bfs(self, monitor)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn solve_parallel(&self) -> Result<Vec<S>, SolverError>
+        where Self: Send + Sync, S: Send + Sync {
+        self.solve_parallel_with_monitor(&mut ())
+    }
+
+    #[cfg(feature = "parallel")]
+    fn solve_parallel_with_monitor<M>(&self, monitor: &mut M) -> Result<Vec<S>, SolverError>
+        where Self: Send + Sync, S: Send + Sync, M: Monitor<S> {
+        if !self.is_solvable() { return Err(Unsolvable); }
+
+        crate::parallel::bfs(self, monitor)
+    }
+
+    fn solve_astar<H>(&self, heuristic: &H) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self> {
+        self.solve_astar_with_monitor(heuristic, &mut ())
+    }
+
+    fn solve_astar_with_monitor<H, M>(&self, heuristic: &H, monitor: &mut M) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self>, M: Monitor<S> {
+        if !self.is_solvable() { return Err(Unsolvable); }
+
+        astar(self, heuristic, WEIGHT_SCALE, monitor, &mut |_: &[S]| false)
+    }
+
+    fn solve_idastar<H>(&self, heuristic: &H) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self> {
+        self.solve_idastar_with_monitor(heuristic, &mut ())
+    }
+
+    fn solve_idastar_with_monitor<H, M>(&self, heuristic: &H, monitor: &mut M) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self>, M: Monitor<S> {
+        if !self.is_solvable() { return Err(Unsolvable); }
+
+        idastar(self, heuristic, monitor)
+    }
+
+    /// A* with f = g + w·h, the solution is at most `weight` times longer than the optimal one
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is lower than 1.
+    fn solve_weighted_astar<H>(&self, heuristic: &H, weight: f64) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self> {
+        self.solve_weighted_astar_with_monitor(heuristic, weight, &mut ())
+    }
+
+    fn solve_weighted_astar_with_monitor<H, M>(&self, heuristic: &H, weight: f64, monitor: &mut M) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self>, M: Monitor<S> {
+        let weight = scaled_weight(weight);
+        if !self.is_solvable() { return Err(Unsolvable); }
+
+        astar(self, heuristic, weight, monitor, &mut |_: &[S]| false)
+    }
 
-        solve_aux(initial, &mut states)
+    /// Weighted A* that goes on after the first solution, until the deadline
+    ///
+    /// Each shorter solution is given to `improved`. The search ends with an optimal solution
+    /// when no state can lead to a shorter one, or with the last solution at the deadline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is lower than 1.
+    fn solve_anytime<H, F>(&self, heuristic: &H, weight: f64, deadline: Instant, improved: F) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self>, F: FnMut(&[S]) {
+        self.solve_anytime_with_monitor(heuristic, weight, &mut Limits::new().deadline(deadline), improved)
+    }
+
+    /// The search stops at the first error of the monitor, with the last solution if any
+    fn solve_anytime_with_monitor<H, M, F>(&self, heuristic: &H, weight: f64, monitor: &mut M, mut improved: F) -> Result<Vec<S>, SolverError>
+        where H: Heuristic<Self>, M: Monitor<S>, F: FnMut(&[S]) {
+        let weight = scaled_weight(weight);
+        if !self.is_solvable() { return Err(Unsolvable); }
+
+        // Anytime weighted A*, going on after each solution
+        astar(self, heuristic, weight, monitor, &mut |steps: &[S]| {
+            improved(steps);
+            true
+        })
+    }
+}
+
+/// A problem with a known goal state, where each step can be undone
+pub trait ReversibleProblem<S: Clone>: Problem<S> {
+    fn goal(&self) -> Self;
+
+    fn reverse_step(&self, step: &S) -> S;
+
+    fn solve_bidirectional(&self) -> Result<Vec<S>, SolverError> {
+        self.solve_bidirectional_with_monitor(&mut ())
+    }
+
+    fn solve_bidirectional_with_monitor<M>(&self, monitor: &mut M) -> Result<Vec<S>, SolverError>
+        where M: Monitor<S> {
+        if !self.is_solvable() { return Err(Unsolvable); }
+
+        bidirectional(self, monitor)
+    }
+
+    /// States whose shortest solution has exactly `distance` steps, in the order of a BFS from the goal
+    fn states_at_distance(&self, distance: u32) -> Result<Vec<Self>, SolverError> {
+        self.states_at_distance_with_monitor(distance, &mut ())
+    }
+
+    fn states_at_distance_with_monitor<M>(&self, distance: u32, monitor: &mut M) -> Result<Vec<Self>, SolverError>
+        where M: Monitor<S> {
+        bfs_level(self.goal(), distance, monitor)
     }
 }
 
 
-fn solve_aux<P, S>(history: Vec<(P, Vec<S>)>, visited: &mut HashSet<P>) -> Result<Vec<S>, SolverError>
-    where P: Problem<S> + Sized + Hash, S: Clone {
-    // Nowhere to go
-    if history.is_empty() { return Err(NoSolutionFound); }
+// BFS
+
+/// Breadth-first search
+///
+/// States are only kept in the visited set and in the frontier,
+/// each visited state only costs a parent pointer to rebuild the path at the end.
+fn bfs<P, S, M>(initial: &P, monitor: &mut M) -> Result<Vec<S>, SolverError>
+    where P: Problem<S>, S: Clone, M: Monitor<S> {
+    let mut parents: Vec<Option<(usize, S)>> = vec![None];
+    let mut visited = HashSet::new();
+    visited.insert(initial.clone());
+    let mut frontier = VecDeque::new();
+    frontier.push_back((initial.clone(), 0, 0));
+    let mut depth = None;
+
+    while let Some((state, index, cost)) = frontier.pop_front() {
+        if depth != Some(cost) {
+            depth = Some(cost);
+            monitor.next_depth(cost);
+        }
 
-    let mut next = vec![];
-    for (state, steps) in history {
         // Try found solution
         if state.is_solved() {
+            let steps = path_from_parents(&parents, index);
+            monitor.found(&steps);
             return Ok(steps);
         }
 
-        // Find next states  FIXME previous
-        let available_steps = state.available_steps(&steps);
-        for step in available_steps {
-            // Apply step
-            let new_state = state.clone().next(step.clone());
+        monitor.check()?;
 
+        let previous = match &parents[index] {
+            Some((_, step)) => std::slice::from_ref(step),
+            None => &[],
+        };
+        let mut new_states = 0;
+        for step in state.available_steps(previous) {
+            let new_state = state.next(step.clone());
             if !visited.contains(&new_state) {
                 // Found a new state
                 visited.insert(new_state.clone());
-                let mut next_history = steps.clone();
-                next_history.push(step.clone());
-                next.push((new_state, next_history));
+                frontier.push_back((new_state, parents.len(), cost + 1));
+                parents.push(Some((index, step)));
+                new_states += 1;
+            }
+        }
+        monitor.expanded(new_states, frontier.len());
+        monitor.visited_states(visited.len());
+    }
+
+    Err(NoSolutionFound)
+}
+
+/// Level of a BFS from the goal
+///
+/// Steps can be undone, so the neighbours of a level are in the previous level,
+/// in the same level or in the next one: only the last two levels are kept.
+fn bfs_level<P, S, M>(goal: P, distance: u32, monitor: &mut M) -> Result<Vec<P>, SolverError>
+    where P: Problem<S>, S: Clone, M: Monitor<S> {
+    let mut previous = HashSet::new();
+    let mut current = HashSet::new();
+    current.insert(goal.clone());
+    let mut level = vec![goal];
+
+    for depth in 0..distance {
+        // Every state is closer to the goal
+        if level.is_empty() { break; }
+
+        monitor.next_depth(depth);
+        let mut next = vec![];
+        let mut seen = HashSet::new();
+        for state in &level {
+            monitor.check()?;
+
+            let mut new_states = 0;
+            for step in state.available_steps(&[]) {
+                let new_state = state.next(step);
+                if !previous.contains(&new_state) && !current.contains(&new_state) && seen.insert(new_state.clone()) {
+                    next.push(new_state);
+                    new_states += 1;
+                }
+            }
+            monitor.expanded(new_states, next.len());
+        }
+        monitor.visited_states(current.len() + next.len());
+
+        previous = std::mem::replace(&mut current, seen);
+        level = next;
+    }
+
+    Ok(level)
+}
+
+pub(crate) fn path_from_parents<S: Clone>(parents: &[Option<(usize, S)>], index: usize) -> Vec<S> {
+    path_back(index, |current| parents[current].as_ref())
+}
+
+/// Steps from the initial state to `index`, going back through the parent of each state
+fn path_back<'a, S, F>(index: usize, parent: F) -> Vec<S>
+    where S: Clone + 'a, F: Fn(usize) -> Option<&'a (usize, S)> {
+    let mut steps = vec![];
+    let mut current = index;
+    while let Some((previous, step)) = parent(current) {
+        steps.push(step.clone());
+        current = *previous;
+    }
+    steps.reverse();
+    steps
+}
+
+// A*
+
+/// Weights are kept as integers, in thousandths
+const WEIGHT_SCALE: u64 = 1000;
+
+fn scaled_weight(weight: f64) -> u64 {
+    assert!(weight.is_finite() && weight >= 1.0, "Weight should be finite and at least 1, got {}", weight);
+    (weight * WEIGHT_SCALE as f64).round() as u64
+}
+
+/// Priority of a node, f = g + w·h scaled by `WEIGHT_SCALE`
+///
+/// Huge weights saturate instead of overflowing.
+fn priority(cost: u32, estimate: u32, weight: u64) -> u64 {
+    (u64::from(cost) * WEIGHT_SCALE).saturating_add(u64::from(estimate).saturating_mul(weight))
+}
+
+struct Node<P, S> {
+    state: P,
+    estimate: u32,
+    parent: Option<(usize, S)>,
+}
+
+/// A*, or weighted A* with a weight above `WEIGHT_SCALE`
+///
+/// The outdated nodes are reopened when a cheaper path is found,
+/// so a weighted solution is at most w times longer than the optimal one.
+///
+/// Each solution is given to `on_solution`, the search ends there unless it returns `true`.
+/// It then goes on only with the states that may lead to a shorter solution:
+/// g + h should be lower than the length of the last solution, h being admissible.
+/// The last solution is returned when the search is over, or when the monitor stops it.
+fn astar<P, S, H, M, F>(initial: &P, heuristic: &H, weight: u64, monitor: &mut M, on_solution: &mut F) -> Result<Vec<S>, SolverError>
+    where P: Problem<S>, S: Clone, H: Heuristic<P>, M: Monitor<S>, F: FnMut(&[S]) -> bool {
+    let estimate = heuristic.estimate(initial);
+    let mut nodes = vec![Node { state: initial.clone(), estimate, parent: None }];
+    let mut best_costs = HashMap::new();
+    best_costs.insert(initial.clone(), 0);
+
+    // Ordered by lowest f, then deepest g to break ties
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((priority(0, estimate, weight), Reverse(0_u32), 0)));
+    let mut depth = None;
+    let mut best: Option<Vec<S>> = None;
+    let is_bounded = |best: &Option<Vec<S>>, length: u32| best.as_ref().is_none_or(|steps| length < steps.len() as u32);
+
+    while let Some(Reverse((f, Reverse(cost), index))) = open.pop() {
+        let node = &nodes[index];
+        // Skip outdated entries, a cheaper path was found meanwhile
+        if best_costs.get(&node.state).is_some_and(|&best| best < cost) {
+            continue;
+        }
+        if !is_bounded(&best, cost.saturating_add(node.estimate)) {
+            continue;
+        }
+        let f = (f / WEIGHT_SCALE) as u32;
+        if depth.is_none_or(|depth| f > depth) {
+            depth = Some(f);
+            monitor.next_depth(f);
+        }
+
+        // Try found solution
+        if node.state.is_solved() {
+            let steps = path_to(&nodes, index);
+            if !on_solution(&steps) {
+                monitor.found(&steps);
+                return Ok(steps);
+            }
+            best = Some(steps);
+            continue;
+        }
+
+        if let Err(error) = monitor.check() {
+            let steps = best.ok_or(error)?;
+            monitor.found(&steps);
+            return Ok(steps);
+        }
+
+        let state = node.state.clone();
+        let previous: Vec<S> = node.parent.iter().map(|(_, step)| step.clone()).collect();
+        let mut new_states = 0;
+        for step in state.available_steps(&previous) {
+            let new_state = state.next(step.clone());
+            let new_cost = cost + 1;
+            let is_better = best_costs.get(&new_state).is_none_or(|&best| new_cost < best);
+            if !is_better { continue; }
+
+            best_costs.insert(new_state.clone(), new_cost);
+            let estimate = heuristic.estimate(&new_state);
+            if is_bounded(&best, new_cost.saturating_add(estimate)) {
+                open.push(Reverse((priority(new_cost, estimate, weight), Reverse(new_cost), nodes.len())));
+                nodes.push(Node { state: new_state, estimate, parent: Some((index, step)) });
+                new_states += 1;
+            }
+        }
+        monitor.expanded(new_states, open.len());
+        monitor.visited_states(best_costs.len());
+    }
+
+    // No state left that could lead to a shorter solution, the last one is optimal
+    let steps = best.ok_or(NoSolutionFound)?;
+    monitor.found(&steps);
+    Ok(steps)
+}
+
+fn path_to<P, S: Clone>(nodes: &[Node<P, S>], index: usize) -> Vec<S> {
+    path_back(index, |current| nodes[current].parent.as_ref())
+}
+
+// IDA*
+
+enum Bound {
+    Found,
+    Exceeded(u32),
+    Exhausted,
+    Stopped(SolverError),
+}
+
+fn idastar<P, S, H, M>(initial: &P, heuristic: &H, monitor: &mut M) -> Result<Vec<S>, SolverError>
+    where P: Problem<S>, S: Clone, H: Heuristic<P>, M: Monitor<S> {
+    let mut threshold = heuristic.estimate(initial);
+    let mut path = vec![];
+
+    loop {
+        monitor.next_depth(threshold);
+        match search_bounded(initial, &mut path, threshold, heuristic, monitor) {
+            Bound::Found => {
+                monitor.found(&path);
+                return Ok(path);
             }
+            Bound::Exceeded(next_threshold) => threshold = next_threshold,
+            Bound::Exhausted => return Err(NoSolutionFound),
+            Bound::Stopped(error) => return Err(error),
+        }
+    }
+}
+
+/// Depth-first search, only the current path is kept in memory
+fn search_bounded<P, S, H, M>(state: &P, path: &mut Vec<S>, threshold: u32, heuristic: &H, monitor: &mut M) -> Bound
+    where P: Problem<S>, S: Clone, H: Heuristic<P>, M: Monitor<S> {
+    let f = path.len() as u32 + heuristic.estimate(state);
+    if f > threshold {
+        return Bound::Exceeded(f);
+    }
+
+    // Try found solution
+    if state.is_solved() {
+        return Bound::Found;
+    }
+
+    if let Err(error) = monitor.check() {
+        return Bound::Stopped(error);
+    }
+
+    let steps = state.available_steps(path);
+    monitor.expanded(steps.len(), path.len());
+    let mut next_threshold = None;
+    for step in steps {
+        let new_state = state.next(step.clone());
+        path.push(step);
+        match search_bounded(&new_state, path, threshold, heuristic, monitor) {
+            Bound::Found => return Bound::Found,
+            Bound::Stopped(error) => return Bound::Stopped(error),
+            Bound::Exceeded(t) => next_threshold = Some(next_threshold.map_or(t, |n: u32| n.min(t))),
+            Bound::Exhausted => {}
         }
+        path.pop();
     }
-    // Deeper
-    solve_aux(next, visited)
-}
\ No newline at end of file
+
+    next_threshold.map_or(Bound::Exhausted, Bound::Exceeded)
+}
+
+// Bidirectional BFS
+
+struct Side<P, S> {
+    parents: Vec<Option<(usize, S)>>,
+    /// Node index and cost of each visited state
+    visited: HashMap<P, (usize, u32)>,
+    frontier: Vec<(P, usize)>,
+    depth: u32,
+}
+
+impl<P, S> Side<P, S> where P: Problem<S>, S: Clone {
+    fn new(state: &P) -> Self {
+        let mut visited = HashMap::new();
+        visited.insert(state.clone(), (0, 0));
+        Side { parents: vec![None], visited, frontier: vec![(state.clone(), 0)], depth: 0 }
+    }
+
+    /// Expand the whole frontier, return the shortest meeting with the other side
+    ///
+    /// The whole level is expanded to keep the shortest meeting, not only the first one.
+    fn expand_level<M>(&mut self, other: &Side<P, S>, monitor: &mut M) -> Result<Option<(usize, usize)>, SolverError>
+        where M: Monitor<S> {
+        let mut best: Option<(u32, usize, usize)> = None;
+        let mut next = vec![];
+        let cost = self.depth + 1;
+        for (state, index) in std::mem::take(&mut self.frontier) {
+            monitor.check()?;
+
+            let previous = match &self.parents[index] {
+                Some((_, step)) => std::slice::from_ref(step),
+                None => &[],
+            };
+            let mut new_states = 0;
+            for step in state.available_steps(previous) {
+                let new_state = state.next(step.clone());
+                if self.visited.contains_key(&new_state) { continue; }
+
+                let new_index = self.parents.len();
+                if let Some(&(other_index, other_cost)) = other.visited.get(&new_state) {
+                    if best.is_none_or(|(best_cost, _, _)| cost + other_cost < best_cost) {
+                        best = Some((cost + other_cost, new_index, other_index));
+                    }
+                }
+                self.visited.insert(new_state.clone(), (new_index, cost));
+                self.parents.push(Some((index, step)));
+                next.push((new_state, new_index));
+                new_states += 1;
+            }
+            monitor.expanded(new_states, next.len());
+        }
+        self.frontier = next;
+        self.depth = cost;
+
+        Ok(best.map(|(_, index, other_index)| (index, other_index)))
+    }
+}
+
+fn bidirectional<P, S, M>(initial: &P, monitor: &mut M) -> Result<Vec<S>, SolverError>
+    where P: ReversibleProblem<S>, S: Clone, M: Monitor<S> {
+    if initial.is_solved() {
+        monitor.found(&[]);
+        return Ok(vec![]);
+    }
+
+    let mut forward = Side::new(initial);
+    let mut backward = Side::new(&initial.goal());
+
+    while !forward.frontier.is_empty() && !backward.frontier.is_empty() {
+        monitor.next_depth(forward.depth + backward.depth);
+
+        // Expand the smallest frontier
+        let meeting = if forward.frontier.len() <= backward.frontier.len() {
+            forward.expand_level(&backward, monitor)?
+        } else {
+            backward.expand_level(&forward, monitor)?
+                .map(|(backward_index, forward_index)| (forward_index, backward_index))
+        };
+        monitor.visited_states(forward.visited.len() + backward.visited.len());
+
+        if let Some((forward_index, backward_index)) = meeting {
+            let mut steps = path_from_parents(&forward.parents, forward_index);
+            let mut backward_steps = path_from_parents(&backward.parents, backward_index);
+            backward_steps.reverse();
+            steps.extend(backward_steps.iter().map(|step| initial.reverse_step(step)));
+
+            monitor.found(&steps);
+            return Ok(steps);
+        }
+    }
+
+    Err(NoSolutionFound)
+}
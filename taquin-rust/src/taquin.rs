@@ -1,21 +1,44 @@
-use std::fmt::{Display, Formatter, Result};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
 
 use rand::prelude::*;
 
-use crate::grid::{Grid, Position, Size};
+use crate::goal::Goal;
+use crate::grid::{Dimensions, Grid, Position, Size};
+use crate::random::{self, SplitMix64};
 use crate::taquin::Tile::{Hole, Value};
-use crate::solver::Problem;
+use crate::packed::PackedTaquin;
+use crate::permutation::{self, MAX_RANKED_VALUES};
+use crate::solver::{Monitor, Problem, ReversibleProblem, SolverError};
 
 
+/// Number written on a tile, a `u16` holds all the tiles of the largest rows and columns
+pub type TileValue = u16;
+
 /// Tile
 #[derive(Hash, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Tile {
     Hole,
-    Value(u8),
+    Value(TileValue),
+}
+
+impl Tile {
+    /// `0` stands for the hole
+    pub fn value(self) -> TileValue {
+        match self {
+            Hole => 0,
+            Value(value) => value,
+        }
+    }
 }
 
 impl Display for Tile {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Hole => write!(f, "·"),
             Value(i) => write!(f, "{}", i)
@@ -23,12 +46,12 @@ impl Display for Tile {
     }
 }
 
-impl Into<Tile> for u8 {
-    fn into(self) -> Tile {
-        if self == 0 {
+impl From<TileValue> for Tile {
+    fn from(value: TileValue) -> Self {
+        if value == 0 {
             Hole
         } else {
-            Value(self)
+            Value(value)
         }
     }
 }
@@ -55,10 +78,31 @@ impl Move {
             Move::Left => Move::Right,
         }
     }
+
+    /// Initial of the move, `U`, `R`, `D` or `L`
+    pub fn letter(self) -> char {
+        match self {
+            Move::Up => 'U',
+            Move::Right => 'R',
+            Move::Down => 'D',
+            Move::Left => 'L',
+        }
+    }
+
+    /// Parse a move from its initial, `U`, `R`, `D` or `L`
+    pub fn from_letter(letter: char) -> Option<Move> {
+        match letter.to_ascii_uppercase() {
+            'U' => Some(Move::Up),
+            'R' => Some(Move::Right),
+            'D' => Some(Move::Down),
+            'L' => Some(Move::Left),
+            _ => None,
+        }
+    }
 }
 
 impl Display for Move {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let c = match self {
             Move::Up => "⬆️",
             Move::Right => "➡️",
@@ -69,62 +113,256 @@ impl Display for Move {
     }
 }
 
+// Parsing
+
+pub const MIN_SIZE: Size = 2;
+pub const MAX_SIZE: Size = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidSize(Dimensions),
+    InvalidSizeDeclaration(String),
+    InvalidTile { index: usize, token: String },
+    OutOfRange { index: usize, value: TileValue },
+    DuplicateTile { index: usize, value: TileValue },
+    WrongTileCount { expected: usize, actual: usize },
+    NotASquare(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidSize(size) =>
+                write!(f, "rows and columns should be between {} and {}, got {}", MIN_SIZE, MAX_SIZE, size),
+            ParseError::InvalidSizeDeclaration(token) =>
+                write!(f, "size is not a number: '{}'", token),
+            ParseError::InvalidTile { index, token } =>
+                write!(f, "tile #{} is not a number: '{}'", index, token),
+            ParseError::OutOfRange { index, value } =>
+                write!(f, "tile #{} is out of range: {}", index, value),
+            ParseError::DuplicateTile { index, value } =>
+                write!(f, "tile #{} is a duplicate: {}", index, value),
+            ParseError::WrongTileCount { expected, actual } =>
+                write!(f, "expected {} tiles, got {}", expected, actual),
+            ParseError::NotASquare(count) =>
+                write!(f, "{} tiles cannot fill a square taquin", count),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
 // Taquin
 
-#[derive(Hash, Clone, Debug, PartialEq, Eq)]
+/// A taquin with its goal, the goal is shared by all the moved taquins
+#[derive(Clone, Debug)]
 pub struct Taquin {
     grid: Grid<Tile>,
+    goal: Arc<Goal>,
 }
 
 impl Taquin {
-    pub fn new(size: Size) -> Self {
-        if size < 2 { panic!("Size should be greater than 2"); }
+    /// Solved taquin, with the sorted goal
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or the columns are not between `MIN_SIZE` and `MAX_SIZE`.
+    pub fn new<D: Into<Dimensions>>(dimensions: D) -> Self {
+        let dimensions = dimensions.into();
+        if let Err(error) = Taquin::check_dimensions(dimensions) { panic!("{}", error); }
+
+        Taquin::solved(Goal::sorted(dimensions))
+    }
+
+    /// Rows and columns should be between `MIN_SIZE` and `MAX_SIZE`
+    pub fn check_dimensions(dimensions: Dimensions) -> Result<(), ParseError> {
+        let valid = |size: Size| (MIN_SIZE..=MAX_SIZE).contains(&size);
+        if valid(dimensions.rows()) && valid(dimensions.columns()) {
+            Ok(())
+        } else {
+            Err(ParseError::InvalidSize(dimensions))
+        }
+    }
+
+    /// Solved taquin for a goal
+    pub fn solved(goal: Goal) -> Self {
+        let grid = Taquin::goal_grid(&goal);
+        Taquin { grid, goal: Arc::new(goal) }
+    }
 
-        let last_index = (size * size - 1) as usize;
-        let mut tiles = vec![];
-        for i in 0..last_index {
-            tiles.push(Value((i + 1) as u8));
+    fn goal_grid(goal: &Goal) -> Grid<Tile> {
+        let tiles = goal.tiles().iter()
+            .map(|&value| Tile::from(value))
+            .collect();
+        Grid::new(goal.dimensions(), tiles)
+    }
+
+    /// Same tiles, solved towards another goal
+    ///
+    /// # Panics
+    ///
+    /// Panics if the goal has other dimensions.
+    pub fn with_goal(self, goal: Goal) -> Self {
+        if goal.dimensions() != self.dimensions() {
+            panic!("Goal size should be {}, got {}", self.dimensions(), goal.dimensions());
         }
-        tiles.push(Hole);
-        let grid = Grid::new(size, tiles);
+        Taquin { grid: self.grid, goal: Arc::new(goal) }
+    }
 
-        Taquin { grid }
+    pub fn target(&self) -> &Goal {
+        &self.goal
     }
 
-    pub fn from_str(size: Size, str: &str) -> Self {
-        if size < 2 { panic!("Size should be greater than 2"); }
+    /// Parse a comma separated list of tiles, `0` stands for the hole
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tiles are not a valid taquin of this size, see `Taquin::try_from` to handle the error.
+    pub fn from_str<D: Into<Dimensions>>(dimensions: D, str: &str) -> Self {
+        Taquin::try_from((dimensions.into(), str))
+            .unwrap_or_else(|error| panic!("Invalid taquin '{}': {}", str, error))
+    }
 
-        let tiles: Vec<Tile> = str.split(',')
-            .map(str::trim)
-            .map(|s| s.parse::<u8>().unwrap())
-            .map(|s| s.into())
+    /// Build from already validated tile values, `0` stands for the hole
+    pub(crate) fn from_values(dimensions: Dimensions, values: &[TileValue]) -> Self {
+        let tiles = values.iter()
+            .map(|&value| Tile::from(value))
             .collect();
-        let last_index = (size * size) as usize;
-        if tiles.len() != last_index { panic!("") }
+        let grid = Grid::new(dimensions, tiles);
 
-        let grid = Grid::new(size, tiles);
-        grid.find(|&tile| tile == Hole)
-            .expect("No Hole found, should contains a '0'");
+        Taquin { grid, goal: Arc::new(Goal::sorted(dimensions)) }
+    }
+
+    fn parse(dimensions: Dimensions, str: &str) -> Result<Self, ParseError> {
+        Taquin::check_dimensions(dimensions)?;
+
+        let tile_count = dimensions.cell_count();
+        let mut seen = HashSet::new();
+        let mut tiles = Vec::with_capacity(tile_count);
+        for (index, token) in str.split(',').map(str::trim).enumerate() {
+            let value = token.parse::<TileValue>()
+                .map_err(|_| ParseError::InvalidTile { index, token: token.to_string() })?;
+            if usize::from(value) >= tile_count {
+                return Err(ParseError::OutOfRange { index, value });
+            }
+            if !seen.insert(value) {
+                return Err(ParseError::DuplicateTile { index, value });
+            }
+            tiles.push(Tile::from(value));
+        }
 
-        Taquin { grid }
+        if tiles.len() != tile_count {
+            return Err(ParseError::WrongTileCount { expected: tile_count, actual: tiles.len() });
+        }
+
+        let grid = Grid::new(dimensions, tiles);
+        Ok(Taquin { grid, goal: Arc::new(Goal::sorted(dimensions)) })
+    }
+
+    /// Lexicographic rank of the tiles row by row, `None` with more than `MAX_RANKED_VALUES` cells
+    ///
+    /// Distinct tiles give distinct ranks, from `0` to `n! - 1`, for dense tables indexed by taquin.
+    pub fn rank(&self) -> Option<u64> {
+        if self.dimensions().cell_count() > MAX_RANKED_VALUES {
+            return None;
+        }
+        Some(permutation::rank(self.grid.iter().map(|&tile| tile.value())))
+    }
+
+    /// Taquin of a goal with the tiles of a rank, it may be unsolvable
+    ///
+    /// `None` if the rank is not lower than `n!` or with more than `MAX_RANKED_VALUES` cells.
+    pub fn unrank(goal: Goal, rank: u64) -> Option<Self> {
+        let dimensions = goal.dimensions();
+        permutation::unrank(dimensions.cell_count(), rank)
+            .map(|values| Taquin::from_values(dimensions, &values).with_goal(goal))
+    }
+
+    /// Taquin drawn uniformly among the solvable ones of a goal, the same for the same seed
+    pub fn random_with_seed(goal: Goal, seed: u64) -> Self {
+        Taquin::random_with_rng(goal, &mut SplitMix64::new(seed))
+    }
+
+    /// Taquin drawn uniformly among the solvable ones of a goal
+    ///
+    /// Unlike `shuffle`, positions far from the goal are as likely as the close ones.
+    pub fn random_with_rng<R: Rng + ?Sized>(goal: Goal, rng: &mut R) -> Self {
+        let mut taquin = Taquin::solved(goal);
+
+        // Fisher-Yates shuffle of the cells
+        let count = taquin.dimensions().cell_count();
+        for index in (1..count).rev() {
+            let other = random::index(rng, index + 1);
+            taquin.swap_cells(index, other);
+        }
+
+        // Swapping two tiles changes the parity, each unsolvable permutation gives one solvable
+        if !taquin.is_solvable() {
+            let values = taquin.tile_values();
+            let mut tiles = (0..count).filter(|&index| values[index] != 0);
+            if let (Some(first), Some(second)) = (tiles.next(), tiles.next()) {
+                taquin.swap_cells(first, second);
+            }
+        }
+        taquin
     }
 
-    pub fn shuffle(&mut self, count: u32) {
+    /// Up to `count` distinct taquins whose optimal solution has exactly `distance` moves, the same for the same seed
+    pub fn at_distance_with_seed(goal: Goal, distance: u32, count: usize, seed: u64) -> Result<Vec<Self>, SolverError> {
+        Taquin::at_distance_with_monitor(goal, distance, count, &mut SplitMix64::new(seed), &mut ())
+    }
+
+    /// Up to `count` distinct taquins whose optimal solution has exactly `distance` moves
+    ///
+    /// All the taquins at this distance are found by a BFS from the goal, then drawn at random.
+    /// The number of taquins grows quickly with the distance, the monitor can limit the search.
+    pub fn at_distance_with_monitor<R, M>(goal: Goal, distance: u32, count: usize, rng: &mut R, monitor: &mut M) -> Result<Vec<Self>, SolverError>
+        where R: Rng + ?Sized, M: Monitor<Move> {
+        let solved = Taquin::solved(goal);
+        match PackedTaquin::try_from(&solved) {
+            Ok(packed) => {
+                let states = packed.states_at_distance_with_monitor(distance, monitor)?;
+                Ok(random::sample(states, count, rng).iter().map(Taquin::from).collect())
+            }
+            Err(_) => {
+                let states = solved.states_at_distance_with_monitor(distance, monitor)?;
+                Ok(random::sample(states, count, rng))
+            }
+        }
+    }
+
+    fn swap_cells(&mut self, index: usize, other: usize) {
+        let position = self.grid.index_to_position(index).expect("Index should be in the grid");
+        let other = self.grid.index_to_position(other).expect("Index should be in the grid");
+        self.grid.swap(&position, &other);
+    }
+
+    /// Apply `count` random moves with a random seed, the seed is returned to replay the shuffle
+    pub fn shuffle(&mut self, count: u32) -> u64 {
+        let seed = thread_rng().gen();
+        self.shuffle_with_seed(count, seed);
+        seed
+    }
+
+    /// Apply `count` random moves, the same ones as the wasm taquin for the same seed
+    pub fn shuffle_with_seed(&mut self, count: u32, seed: u64) {
+        self.shuffle_with_rng(count, &mut SplitMix64::new(seed));
+    }
+
+    /// Apply `count` random moves, never undoing the previous one unless it is the only move
+    pub fn shuffle_with_rng<R: Rng + ?Sized>(&mut self, count: u32, rng: &mut R) {
         let mut hole_position = self.find_hole();
         let mut last_move = None::<Move>;
-        let mut rng = thread_rng();
 
         for _i in 0..count {
-            // Valid moves
-            let valid_moves = self.valid_moves(last_move.as_ref());
+            // Valid moves, going back from a dead end
+            let mut valid_moves = self.valid_moves(last_move.as_ref());
+            if valid_moves.is_empty() {
+                valid_moves = self.valid_moves(None);
+            }
 
             // Shuffle
-            let index = if valid_moves.len() == 1 {
-                0
-            } else {
-                rng.gen_range(0, valid_moves.len() - 1)
-            };
-            let current_move = valid_moves[index];
+            let current_move = valid_moves[random::index(rng, valid_moves.len())];
 
             // update
             let position = Taquin::apply_move(&hole_position, current_move);
@@ -135,20 +373,94 @@ impl Taquin {
         }
     }
 
-    pub fn size(&self) -> Size {
-        self.grid.size()
+    pub fn dimensions(&self) -> Dimensions {
+        self.grid.dimensions()
+    }
+
+    pub fn rows(&self) -> Size {
+        self.grid.rows()
+    }
+
+    pub fn columns(&self) -> Size {
+        self.grid.columns()
+    }
+
+    /// Tiles row by row, `0` stands for the hole
+    pub fn tile_values(&self) -> Vec<TileValue> {
+        self.grid.iter()
+            .map(|&tile| tile.value())
+            .collect()
+    }
+
+    /// Comma separated tiles, as read by `Taquin::from_str`
+    pub fn to_line(&self) -> String {
+        let columns = usize::from(self.columns());
+        let tiles: Vec<String> = self.tile_values().iter()
+            .map(TileValue::to_string)
+            .collect();
+        tiles.chunks(columns)
+            .map(|row| row.join(","))
+            .collect::<Vec<String>>()
+            .join(",  ")
     }
 
     pub fn is_solved(&self) -> bool {
-        let last_index = (self.size() * self.size() - 1) as usize;
+        self.grid.iter()
+            .zip(self.goal.tiles())
+            .all(|(&tile, &value)| tile == Tile::from(value))
+    }
+
+    /// Number of pairs of tiles in the wrong order, the hole is ignored
+    pub fn count_inversions(&self) -> u32 {
+        let values: Vec<TileValue> = self.grid.iter()
+            .filter_map(|&tile| match tile {
+                Hole => None,
+                Value(value) => Some(value),
+            })
+            .collect();
+
+        let mut count = 0;
+        for (i, value) in values.iter().enumerate() {
+            count += values[i + 1..].iter().filter(|&other| other < value).count() as u32;
+        }
+        count
+    }
+
+    /// A move swaps the hole with a tile and moves the hole by one cell, so the parity of
+    /// the permutation from the goal (the hole included) plus the distance of the hole
+    /// to its goal position should be even, as for the solved taquin.
+    pub fn is_solvable(&self) -> bool {
+        let values = self.tile_values();
+        let mut visited = vec![false; values.len()];
+        let mut transpositions = 0;
+        for start in 0..values.len() {
+            let mut index = start;
+            while !visited[index] {
+                visited[index] = true;
+                index = self.goal.position(values[index]);
+                if index != start { transpositions += 1; }
+            }
+        }
+
+        let columns = usize::from(self.columns());
+        let hole = self.goal.position(0);
+        let current = self.find_hole();
+        let hole_distance = usize::from(current.row()).abs_diff(hole / columns)
+            + usize::from(current.column()).abs_diff(hole % columns);
+        (transpositions + hole_distance).is_multiple_of(2)
+    }
+
+    /// Sum of the distances of each tile to its goal position
+    pub fn manhattan_distance(&self) -> u32 {
+        let columns = usize::from(self.columns());
+        let mut distance = 0;
         for (i, &tile) in self.grid.iter().enumerate() {
-            if i == last_index {
-                return tile == Hole;
-            } else if tile != Value((i + 1) as u8) {
-                return false;
-            };
+            if let Value(value) = tile {
+                let target = self.goal.position(value);
+                distance += (i / columns).abs_diff(target / columns) + (i % columns).abs_diff(target % columns);
+            }
         }
-        false
+        distance as u32
     }
 
     fn find_hole(&self) -> Position {
@@ -156,16 +468,23 @@ impl Taquin {
             .expect("No Hole found !")
     }
 
+    /// Move the hole, an invalid move keeps the taquin unchanged
     pub fn move_hole(&self, user_move: Move) -> Self {
+        self.try_move_hole(user_move)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// Move the hole, or `None` if the move is invalid
+    pub fn try_move_hole(&self, user_move: Move) -> Option<Self> {
         let hole_position = self.find_hole();
 
         if self.is_valid(user_move, &hole_position) {
             let position = Taquin::apply_move(&hole_position, user_move);
             let mut new_grid = self.grid.clone();
             new_grid.swap(&position, &hole_position);
-            Taquin { grid: new_grid }
+            Some(Taquin { grid: new_grid, goal: self.goal.clone() })
         } else {
-            self.clone()
+            None
         }
     }
 
@@ -185,10 +504,10 @@ impl Taquin {
         let row = hole_position.row();
         let column = hole_position.column();
         match user_move {
-            Move::Up => row < self.size() - 1,
+            Move::Up => row < self.rows() - 1,
             Move::Right => column > 0,
             Move::Down => row > 0,
-            Move::Left => column < self.size() - 1,
+            Move::Left => column < self.columns() - 1,
         }
     }
 
@@ -197,7 +516,7 @@ impl Taquin {
 
         let mut valid_moves = vec![];
         for m in Move::all() {
-            let not_back = last_move.map_or(true, |last| last != &m.reverse());
+            let not_back = last_move != Some(&m.reverse());
             if not_back && self.is_valid(m, &hole_position) {
                 valid_moves.push(m);
             }
@@ -206,21 +525,71 @@ impl Taquin {
     }
 }
 
+/// Only the tiles are hashed, the goal is usually the same
+///
+/// The rank is hashed when it fits, a single `u64` instead of all the tiles.
+impl Hash for Taquin {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.rank() {
+            Some(rank) => rank.hash(state),
+            None => self.grid.hash(state),
+        }
+    }
+}
+
+impl PartialEq for Taquin {
+    fn eq(&self, other: &Self) -> bool {
+        self.grid == other.grid && (Arc::ptr_eq(&self.goal, &other.goal) || self.goal == other.goal)
+    }
+}
+
+impl Eq for Taquin {}
+
 impl Display for Taquin {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let size = self.size();
-        let width = ((size * size) as f64).log10().floor() as usize + 1;
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (rows, columns) = (self.rows(), self.columns());
+        let width = (self.dimensions().cell_count() as f64).log10().floor() as usize + 1;
 
         for (i, value) in self.grid.iter().enumerate() {
             let pos = self.grid.index_to_position(i).unwrap();
             if pos.column() > 0 { write!(f, " ")?; }
             write!(f, "{:width$}", value, width = width)?;
-            if pos.column() == (size - 1) && pos.row() < (size - 1) { writeln!(f, "")?; }
+            if pos.column() == (columns - 1) && pos.row() < (rows - 1) { writeln!(f)?; }
         }
         Ok(())
     }
 }
 
+impl TryFrom<(Size, &str)> for Taquin {
+    type Error = ParseError;
+
+    fn try_from((size, str): (Size, &str)) -> Result<Self, Self::Error> {
+        Taquin::parse(Dimensions::from(size), str)
+    }
+}
+
+impl TryFrom<(Dimensions, &str)> for Taquin {
+    type Error = ParseError;
+
+    fn try_from((dimensions, str): (Dimensions, &str)) -> Result<Self, Self::Error> {
+        Taquin::parse(dimensions, str)
+    }
+}
+
+/// Parse a comma separated list of tiles, the size of the square is inferred from the number of tiles
+impl FromStr for Taquin {
+    type Err = ParseError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let count = str.split(',').count();
+        let size = (MIN_SIZE..=MAX_SIZE)
+            .find(|&size| usize::from(size).pow(2) == count)
+            .ok_or(ParseError::NotASquare(count))?;
+
+        Taquin::parse(Dimensions::from(size), str)
+    }
+}
+
 // Solve
 impl Problem<Move> for Taquin {
     fn is_solved(&self) -> bool {
@@ -234,6 +603,21 @@ impl Problem<Move> for Taquin {
     fn next(&self, step: Move) -> Self {
         self.move_hole(step)
     }
+
+    fn is_solvable(&self) -> bool {
+        self.is_solvable()
+    }
+}
+
+impl ReversibleProblem<Move> for Taquin {
+    fn goal(&self) -> Self {
+        let grid = Taquin::goal_grid(&self.goal);
+        Taquin { grid, goal: self.goal.clone() }
+    }
+
+    fn reverse_step(&self, step: &Move) -> Move {
+        step.reverse()
+    }
 }
 
 #[cfg(test)]
@@ -249,7 +633,7 @@ mod tests {
                 Value(8), Value(1), Value(2),
                 Value(4), Value(7), Value(6), ]);
 
-            Taquin { grid }
+            Taquin { grid, goal: Arc::new(Goal::sorted(3)) }
         }
     }
 
@@ -262,6 +646,11 @@ mod tests {
             assert_eq!(Hole, 0.into());
             assert_eq!(Hole, 0.into());
         }
+
+        #[test]
+        fn large_value() {
+            assert_eq!(Value(1023), 1023.into());
+        }
     }
 
     mod moves {
@@ -272,6 +661,22 @@ mod tests {
             assert_eq!(Move::all().len(), 4);
         }
 
+        #[test]
+        fn letter() {
+            for m in Move::all() {
+                assert_eq!(Move::from_letter(m.letter()), Some(m));
+            }
+        }
+
+        #[test]
+        fn from_letter() {
+            assert_eq!(Move::from_letter('U'), Some(Move::Up));
+            assert_eq!(Move::from_letter('r'), Some(Move::Right));
+            assert_eq!(Move::from_letter('D'), Some(Move::Down));
+            assert_eq!(Move::from_letter('L'), Some(Move::Left));
+            assert_eq!(Move::from_letter('X'), None);
+        }
+
         #[test]
         fn reverse() {
             assert_eq!(Move::Up.reverse(), Move::Down);
@@ -299,6 +704,14 @@ mod tests {
                 Taquin::from_str(1, "5,0,3,  8,1,2,  4,7,6");
             }
 
+            #[test]
+            fn large() {
+                let line = Taquin::new(16).to_line().replace("254,255,  0", "254,0,  255");
+                let taquin = Taquin::from_str(16, &line);
+                assert_eq!(taquin, Taquin::new(16).move_hole(Move::Left));
+                assert_eq!(taquin.to_line(), line);
+            }
+
             #[test]
             #[should_panic]
             fn fail_content() {
@@ -312,6 +725,132 @@ mod tests {
             }
         }
 
+        mod new {
+            use super::*;
+
+            #[test]
+            fn large() {
+                let taquin = Taquin::new(32);
+                assert!(taquin.is_solved());
+                assert!(taquin.is_solvable());
+                assert_eq!(taquin.tile_values()[1022], 1023);
+
+                let taquin = taquin.move_hole(Move::Down).move_hole(Move::Right);
+                assert!(!taquin.is_solved());
+                assert!(taquin.is_solvable());
+                assert_eq!(taquin.manhattan_distance(), 2);
+            }
+
+            #[test]
+            #[should_panic(expected = "rows and columns should be between 2 and 32, got 33")]
+            fn too_large() {
+                Taquin::new(33);
+            }
+        }
+
+        mod try_from {
+            use super::*;
+
+            #[test]
+            fn ok() {
+                let taquin = Taquin::try_from((3, "5,0,3,  8,1,2,  4,7,6"));
+                assert_eq!(taquin, Ok(given::a_taquin()));
+            }
+
+            #[test]
+            fn rectangle() {
+                let taquin = Taquin::try_from((Dimensions::new(2, 4), "1,2,3,4,  5,6,7,0"));
+                assert_eq!(taquin, Ok(Taquin::new(Dimensions::new(2, 4))));
+            }
+
+            #[test]
+            fn invalid_size() {
+                let taquin = Taquin::try_from((1, "0"));
+                assert_eq!(taquin, Err(ParseError::InvalidSize(Dimensions::from(1))));
+
+                let taquin = Taquin::try_from((Dimensions::new(1, 4), "1,2,3,0"));
+                assert_eq!(taquin, Err(ParseError::InvalidSize(Dimensions::new(1, 4))));
+
+                let taquin = Taquin::try_from((33, "0"));
+                assert_eq!(taquin, Err(ParseError::InvalidSize(Dimensions::from(33))));
+            }
+
+            #[test]
+            fn invalid_tile() {
+                let taquin = Taquin::try_from((3, "5,0,3,  8,x,2,  4,7,6"));
+                assert_eq!(taquin, Err(ParseError::InvalidTile { index: 4, token: String::from("x") }));
+            }
+
+            #[test]
+            fn out_of_range() {
+                let taquin = Taquin::try_from((3, "5,0,3,  8,1,2,  4,7,9"));
+                assert_eq!(taquin, Err(ParseError::OutOfRange { index: 8, value: 9 }));
+            }
+
+            #[test]
+            fn duplicate_tile() {
+                let taquin = Taquin::try_from((3, "5,0,3,  8,1,2,  4,7,5"));
+                assert_eq!(taquin, Err(ParseError::DuplicateTile { index: 8, value: 5 }));
+            }
+
+            #[test]
+            fn wrong_tile_count() {
+                let taquin = Taquin::try_from((3, "5,0,3,  8,1,2,  4,7"));
+                assert_eq!(taquin, Err(ParseError::WrongTileCount { expected: 9, actual: 8 }));
+            }
+        }
+
+        mod parse {
+            use super::*;
+
+            #[test]
+            fn ok() {
+                let taquin = "5,0,3,  8,1,2,  4,7,6".parse::<Taquin>();
+                assert_eq!(taquin, Ok(given::a_taquin()));
+
+                let taquin = "1,2,3,4,  5,6,7,8,  9,10,11,12,  13,14,15,0".parse::<Taquin>();
+                assert_eq!(taquin, Ok(Taquin::new(4)));
+            }
+
+            #[test]
+            fn not_a_square() {
+                let taquin = "5,0,3,  8,1,2,  4,7".parse::<Taquin>();
+                assert_eq!(taquin, Err(ParseError::NotASquare(8)));
+            }
+        }
+
+        mod to_line {
+            use super::*;
+
+            #[test]
+            fn ok() {
+                let line = given::a_taquin().to_line();
+                assert_eq!(line, "5,0,3,  8,1,2,  4,7,6");
+                assert_eq!(Taquin::from_str(3, &line), given::a_taquin());
+            }
+
+            #[test]
+            fn rectangle() {
+                let taquin = Taquin::from_str(Dimensions::new(3, 2), "1,2,  0,4,  3,5");
+                assert_eq!(taquin.to_line(), "1,2,  0,4,  3,5");
+            }
+        }
+
+        mod display {
+            use super::*;
+
+            #[test]
+            fn ok() {
+                assert_eq!(given::a_taquin().to_string(), "5 · 3\n8 1 2\n4 7 6");
+            }
+
+            #[test]
+            fn rectangle() {
+                let taquin = Taquin::new(Dimensions::new(2, 5));
+                assert_eq!(taquin.to_string(), "1 2 3 4 5\n6 7 8 9 ·");
+            }
+        }
+
         mod is_solved {
             use super::*;
 
@@ -326,6 +865,93 @@ mod tests {
                 let taquin = given::a_taquin();
                 assert!(!taquin.is_solved())
             }
+
+            #[test]
+            fn with_goal() {
+                let taquin = Taquin::solved(Goal::spiral(3));
+                assert_eq!(taquin.to_line(), "1,2,3,  8,0,4,  7,6,5");
+                assert!(taquin.is_solved());
+                assert!(!Taquin::new(3).with_goal(Goal::spiral(3)).is_solved());
+            }
+        }
+
+        mod count_inversions {
+            use super::*;
+
+            #[test]
+            fn solved() {
+                let taquin = Taquin::new(3);
+                assert_eq!(taquin.count_inversions(), 0);
+            }
+
+            #[test]
+            fn unsolved() {
+                let taquin = given::a_taquin();
+                assert_eq!(taquin.count_inversions(), 12);
+            }
+        }
+
+        mod is_solvable {
+            use super::*;
+
+            #[test]
+            fn solvable() {
+                assert!(Taquin::new(3).is_solvable());
+                assert!(Taquin::new(4).is_solvable());
+                assert!(given::a_taquin().is_solvable());
+                assert!(Taquin::from_str(4, "1,2,3,4,  5,6,7,8,  9,10,11,0,  13,14,15,12").is_solvable());
+                assert!(Taquin::from_str(4, "1,2,3,4,  5,6,7,8,  9,10,11,12,  0,13,14,15").is_solvable());
+            }
+
+            #[test]
+            fn unsolvable() {
+                assert!(!Taquin::from_str(3, "2,1,3,  4,5,6,  7,8,0").is_solvable());
+                assert!(!Taquin::from_str(4, "2,1,3,4,  5,6,7,8,  9,10,11,12,  13,14,15,0").is_solvable());
+                assert!(!Taquin::from_str(4, "1,2,3,4,  5,6,7,8,  9,10,11,0,  12,13,14,15").is_solvable());
+            }
+
+            #[test]
+            fn with_goal() {
+                // the sorted and hole first goals are not reachable from each other for an even size
+                assert!(Taquin::new(3).with_goal(Goal::hole_first(3)).is_solvable());
+                assert!(!Taquin::new(4).with_goal(Goal::hole_first(4)).is_solvable());
+                assert!(!Taquin::new(3).with_goal(Goal::spiral(3)).is_solvable());
+                assert!(Taquin::new(4).with_goal(Goal::spiral(4)).is_solvable());
+            }
+
+            #[test]
+            fn rectangle() {
+                let dimensions = Dimensions::new(2, 3);
+                assert!(Taquin::from_str(dimensions, "1,2,3,  0,4,5").is_solvable());
+                assert!(!Taquin::from_str(dimensions, "2,1,3,  4,5,0").is_solvable());
+
+                // with an even number of columns, the row of the hole counts
+                let dimensions = Dimensions::new(3, 4);
+                assert!(Taquin::from_str(dimensions, "1,2,3,4,  5,6,7,0,  9,10,11,8").is_solvable());
+                assert!(!Taquin::from_str(dimensions, "1,2,3,4,  5,6,7,8,  9,11,10,0").is_solvable());
+            }
+        }
+
+        mod manhattan_distance {
+            use super::*;
+
+            #[test]
+            fn solved() {
+                let taquin = Taquin::new(3);
+                assert_eq!(taquin.manhattan_distance(), 0);
+            }
+
+            #[test]
+            fn unsolved() {
+                let taquin = given::a_taquin();
+                assert_eq!(taquin.manhattan_distance(), 11);
+            }
+
+            #[test]
+            fn with_goal() {
+                let taquin = Taquin::new(3).with_goal(Goal::hole_first(3));
+                assert_eq!(taquin.manhattan_distance(), 12);
+            }
         }
 
         mod find_hole {
@@ -362,6 +988,33 @@ mod tests {
                 let taquin = taquin.move_hole(m);
                 assert_eq!(taquin, Taquin::from_str(3, "1,0,3,  5,8,2,  4,7,6"), "Move {} ", m);
             }
+
+            #[test]
+            fn rectangle() {
+                let taquin = Taquin::new(Dimensions::new(2, 3));
+                let taquin = taquin.move_hole(Move::Down);
+                assert_eq!(taquin, Taquin::from_str(Dimensions::new(2, 3), "1,2,0,  4,5,3"));
+                let taquin = taquin.move_hole(Move::Right);
+                assert_eq!(taquin, Taquin::from_str(Dimensions::new(2, 3), "1,0,2,  4,5,3"));
+                assert_eq!(taquin.try_move_hole(Move::Down), None);
+            }
+        }
+
+        mod try_move_hole {
+            use super::*;
+
+            #[test]
+            fn ok() {
+                let taquin = given::a_taquin();
+                let moved = taquin.try_move_hole(Move::Up);
+                assert_eq!(moved, Some(Taquin::from_str(3, "5,1,3,  8,0,2,  4,7,6")));
+            }
+
+            #[test]
+            fn invalid() {
+                let taquin = given::a_taquin();
+                assert_eq!(taquin.try_move_hole(Move::Down), None);
+            }
         }
 
         mod is_valid {
@@ -420,5 +1073,121 @@ mod tests {
                 assert_eq!(moves, vec![Move::Right, Move::Left])
             }
         }
+
+        mod rank {
+            use super::*;
+
+            #[test]
+            fn ok() {
+                assert_eq!(Taquin::solved(Goal::hole_first(3)).rank(), Some(0));
+                assert_eq!(Taquin::from_str(2, "3,2,1,0").rank(), Some(23));
+                assert_eq!(given::a_taquin().rank(), Some(203_641));
+            }
+
+            #[test]
+            fn too_large() {
+                assert!(Taquin::new(Dimensions::new(4, 5)).rank().is_some());
+                assert_eq!(Taquin::new(Dimensions::new(3, 7)).rank(), None);
+            }
+
+            #[test]
+            fn same_hash_set() {
+                let taquins: HashSet<Taquin> = (0..24)
+                    .filter_map(|rank| Taquin::unrank(Goal::sorted(2), rank))
+                    .collect();
+                assert_eq!(taquins.len(), 24);
+                assert!(taquins.contains(&Taquin::new(2)));
+            }
+        }
+
+        mod unrank {
+            use super::*;
+
+            #[test]
+            fn round_trip() {
+                let taquin = given::a_taquin();
+                let rank = taquin.rank().expect("Should be ranked");
+                assert_eq!(Taquin::unrank(Goal::sorted(3), rank), Some(taquin));
+            }
+
+            #[test]
+            fn with_goal() {
+                let taquin = Taquin::unrank(Goal::hole_first(3), 0).expect("Rank should be valid");
+                assert!(taquin.is_solved());
+            }
+
+            #[test]
+            fn out_of_range() {
+                assert_eq!(Taquin::unrank(Goal::sorted(2), 24), None);
+            }
+        }
+
+        mod random_with_seed {
+            use super::*;
+            use std::collections::HashMap;
+
+            #[test]
+            fn solvable() {
+                for seed in 0..100 {
+                    assert!(Taquin::random_with_seed(Goal::sorted(4), seed).is_solvable());
+                    assert!(Taquin::random_with_seed(Goal::sorted(Dimensions::new(2, 3)), seed).is_solvable());
+                    assert!(Taquin::random_with_seed(Goal::hole_first(3), seed).is_solvable());
+                }
+            }
+
+            #[test]
+            fn same_seed() {
+                let taquin = Taquin::random_with_seed(Goal::sorted(5), 42);
+                assert_eq!(taquin, Taquin::random_with_seed(Goal::sorted(5), 42));
+                assert_ne!(taquin, Taquin::random_with_seed(Goal::sorted(5), 43));
+            }
+
+            #[test]
+            fn uniform() {
+                // the 12 solvable 2x2 taquins are drawn about 1000 times each
+                let mut counts = HashMap::new();
+                for seed in 0..12_000 {
+                    let taquin = Taquin::random_with_seed(Goal::sorted(2), seed);
+                    *counts.entry(taquin.to_line()).or_insert(0) += 1;
+                }
+                assert_eq!(counts.len(), 12);
+                assert!(counts.values().all(|&count| (850..1150).contains(&count)), "{:?}", counts);
+            }
+        }
+
+        mod shuffle_with_seed {
+            use super::*;
+
+            #[test]
+            fn same_as_wasm() {
+                // same tiles in the test of the wasm crate
+                let mut taquin = Taquin::new(3);
+                taquin.shuffle_with_seed(30, 2021);
+                assert_eq!(taquin.to_line(), "6,4,1,  5,0,3,  7,2,8");
+            }
+
+            #[test]
+            fn replay() {
+                let mut taquin = Taquin::new(4);
+                let seed = taquin.shuffle(100);
+                let mut replayed = Taquin::new(4);
+                replayed.shuffle_with_seed(100, seed);
+                assert_eq!(taquin, replayed);
+            }
+
+            #[test]
+            fn corridor() {
+                let mut taquin = Taquin::solved(Goal::sorted(Dimensions::new(1, 5)));
+                taquin.shuffle_with_seed(20, 3);
+                assert!(taquin.is_solvable());
+            }
+
+            #[test]
+            fn stay_solvable() {
+                let mut taquin = Taquin::new(Dimensions::new(3, 5));
+                taquin.shuffle_with_seed(200, 7);
+                assert!(taquin.is_solvable());
+            }
+        }
     }
 }
\ No newline at end of file
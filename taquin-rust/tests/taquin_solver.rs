@@ -1,5 +1,18 @@
+use taquin_rust::distance::DistanceDatabase;
+use taquin_rust::goal::Goal;
+use taquin_rust::grid::Dimensions;
 use taquin_rust::taquin::Taquin;
-use taquin_rust::solver::Problem;
+use taquin_rust::solver::{CancellationToken, Limits, Monitor, Problem, ReversibleProblem, SolverError, Stats};
+use std::time::Instant;
+use taquin_rust::taquin::Move;
+use taquin_rust::heuristic::{LinearConflict, ManhattanDistance, WalkingDistance};
+use taquin_rust::packed::PackedTaquin;
+use taquin_rust::pattern::PatternDatabase;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::BufReader;
+use taquin_rust::input;
+use taquin_rust::solver::Heuristic;
 
 #[test]
 fn already_solved() {
@@ -20,11 +33,10 @@ fn solve_one_move() {
 #[test]
 fn solve_few_moves() {
     let mut taquin = Taquin::new(3);
-    taquin.shuffle(5);
+    taquin.shuffle_with_seed(5, 2021);
     let result = taquin.solve().expect("Oops!");
     println!("Solve in {} moves", result.len());
     assert_eq!(result.len(), 5);
-    // assert!(result.len() <= 5)
 }
 
 #[test]
@@ -39,3 +51,597 @@ fn solve_a_3x3() {
 
     assert!(end.is_solved())
 }
+
+#[test]
+fn solve_uniform_random() {
+    for seed in 0..5 {
+        let taquin = Taquin::random_with_seed(Goal::sorted(3), seed);
+        let result = taquin.solve_astar(&ManhattanDistance).expect("Oops!");
+        assert!(result.len() <= 31);
+
+        let end = result.iter()
+            .fold(taquin, |t, m| t.move_hole(*m));
+        assert!(end.is_solved())
+    }
+}
+
+#[test]
+fn exact_distance_3x3() {
+    let taquins = Taquin::at_distance_with_seed(Goal::sorted(3), 20, 5, 42).expect("Oops!");
+    assert_eq!(taquins.len(), 5);
+    for taquin in taquins {
+        let result = taquin.solve_astar(&ManhattanDistance).expect("Oops!");
+        assert_eq!(result.len(), 20);
+    }
+}
+
+#[test]
+fn exact_distance_farthest() {
+    // the two hardest 3x3 taquins
+    let taquins = Taquin::at_distance_with_seed(Goal::sorted(3), 31, 10, 0).expect("Oops!");
+    assert_eq!(taquins.len(), 2);
+    assert!(Taquin::at_distance_with_seed(Goal::sorted(3), 32, 10, 0).expect("Oops!").is_empty());
+    assert!(Taquin::at_distance_with_seed(Goal::sorted(3), 4_000_000_000, 10, 0).expect("Oops!").is_empty());
+}
+
+#[test]
+fn exact_distance_states() {
+    let taquin = Taquin::new(Dimensions::new(2, 3));
+    let counts: Vec<usize> = (0..4)
+        .map(|distance| taquin.states_at_distance(distance).expect("Oops!").len())
+        .collect();
+    assert_eq!(counts, vec![1, 2, 3, 5]);
+}
+
+#[test]
+fn exact_distance_limit() {
+    let mut limits = Limits::new().max_visited(1000);
+    let mut rng = taquin_rust::random::SplitMix64::new(0);
+    let result = Taquin::at_distance_with_monitor(Goal::sorted(4), 30, 1, &mut rng, &mut limits);
+    assert!(matches!(result, Err(SolverError::LimitReached { .. })));
+}
+
+#[test]
+fn solver_error_display() {
+    let error = SolverError::LimitReached { expanded: 860, depth: 9 };
+    assert_eq!(error.to_string(), "limit reached after expanding 860 states, at depth 9");
+    assert_eq!(SolverError::Unsolvable.to_string(), "unsolvable");
+}
+
+
+#[test]
+fn astar_already_solved() {
+    let taquin = Taquin::new(3);
+    let result = taquin.solve_astar(&ManhattanDistance);
+    assert_eq!(result, Ok(vec![]))
+}
+
+#[test]
+fn astar_solve_a_3x3() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let result = taquin.solve_astar(&ManhattanDistance).expect("Oops!");
+    assert_eq!(result.len(), 17);
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+
+    assert!(end.is_solved())
+}
+
+#[test]
+fn astar_same_length_as_bfs() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let bfs = taquin.solve().expect("Oops!");
+    let astar = taquin.solve_astar(&ManhattanDistance).expect("Oops!");
+    assert_eq!(astar.len(), bfs.len())
+}
+
+#[test]
+fn table_same_length_as_astar() {
+    let database = DistanceDatabase::new();
+    for seed in 0..5 {
+        let taquin = Taquin::random_with_seed(Goal::sorted(3), seed);
+        let table = database.solve(&taquin).expect("Oops!");
+        let astar = taquin.solve_astar(&ManhattanDistance).expect("Oops!");
+        assert_eq!(table.len(), astar.len());
+        assert_eq!(database.distance(&taquin), Some(astar.len() as u32));
+    }
+}
+
+#[test]
+fn table_as_heuristic() {
+    let database = DistanceDatabase::new();
+    let taquin = Taquin::from_str(3, "6,4,7,  8,5,0,  3,2,1");
+    let packed = PackedTaquin::try_from(&taquin).expect("Oops!");
+    let mut stats = Stats::default();
+    let result = packed.solve_astar_with_monitor(&database, &mut stats).expect("Oops!");
+    assert_eq!(result.len(), 31);
+    // only the states of optimal solutions are expanded
+    assert!(stats.expanded < 100);
+}
+
+#[test]
+fn astar_solve_a_4x4() {
+    let taquin = Taquin::from_str(4, "2,3,4,8,  1,6,7,12,  5,10,11,0,  9,13,14,15");
+    let result = taquin.solve_astar(&ManhattanDistance).expect("Oops!");
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+
+    assert!(end.is_solved())
+}
+
+#[test]
+fn idastar_already_solved() {
+    let taquin = Taquin::new(3);
+    let result = taquin.solve_idastar(&ManhattanDistance);
+    assert_eq!(result, Ok(vec![]))
+}
+
+#[test]
+fn idastar_same_length_as_bfs() {
+    let taquin = Taquin::from_str(3, "6,4,7,  8,5,0,  3,2,1");
+    let bfs = taquin.solve().expect("Oops!");
+    let idastar = taquin.solve_idastar(&ManhattanDistance).expect("Oops!");
+    assert_eq!(idastar.len(), bfs.len())
+}
+
+#[test]
+fn idastar_solve_a_4x4() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let result = taquin.solve_idastar(&ManhattanDistance).expect("Oops!");
+    assert_eq!(result.len(), 40);
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+
+    assert!(end.is_solved())
+}
+
+#[test]
+fn unsolvable_3x3() {
+    let taquin = Taquin::from_str(3, "2,1,3,  4,5,6,  7,8,0");
+    assert_eq!(taquin.solve(), Err(SolverError::Unsolvable));
+    assert_eq!(taquin.solve_astar(&ManhattanDistance), Err(SolverError::Unsolvable));
+    assert_eq!(taquin.solve_idastar(&ManhattanDistance), Err(SolverError::Unsolvable));
+}
+
+#[test]
+fn unsolvable_4x4() {
+    let taquin = Taquin::from_str(4, "1,2,0,14,  13,9,4,8,  15,10,11,12,  7,5,6,3");
+    assert_eq!(taquin.solve_idastar(&ManhattanDistance), Err(SolverError::Unsolvable));
+}
+
+#[derive(Default)]
+struct FoundMonitor {
+    depths: Vec<u32>,
+    found: Option<Vec<Move>>,
+}
+
+impl Monitor<Move> for FoundMonitor {
+    fn next_depth(&mut self, depth: u32) {
+        self.depths.push(depth);
+    }
+
+    fn found(&mut self, steps: &[Move]) {
+        self.found = Some(steps.to_vec());
+    }
+}
+
+#[test]
+fn monitor_bfs() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let mut stats = Stats::default();
+    let mut found = FoundMonitor::default();
+    let result = taquin.solve_with_monitor(&mut (&mut stats).and(&mut found)).expect("Oops!");
+
+    assert_eq!(stats.depth, 17);
+    assert!(stats.expanded > 0);
+    assert!(stats.max_frontier > 0);
+    assert_eq!(found.depths, (0..=17).collect::<Vec<u32>>());
+    assert_eq!(found.found, Some(result));
+}
+
+#[test]
+fn monitor_astar() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let mut stats = Stats::default();
+    let result = taquin.solve_astar_with_monitor(&ManhattanDistance, &mut stats).expect("Oops!");
+
+    assert_eq!(stats.depth, result.len() as u32);
+    assert!(stats.expanded > 0);
+}
+
+#[test]
+fn monitor_idastar() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let mut stats = Stats::default();
+    let mut found = FoundMonitor::default();
+    let result = taquin.solve_idastar_with_monitor(&ManhattanDistance, &mut (&mut stats).and(&mut found)).expect("Oops!");
+
+    assert_eq!(stats.depth, result.len() as u32);
+    assert_eq!(stats.max_frontier, result.len() - 1);
+    assert_eq!(found.found, Some(result));
+}
+
+#[test]
+fn limit_max_expanded() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let mut limits = Limits::new().max_expanded(10);
+    let result = taquin.solve_with_monitor(&mut limits);
+    assert!(matches!(result, Err(SolverError::LimitReached { expanded: 10, .. })));
+}
+
+#[test]
+fn limit_max_depth() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let mut limits = Limits::new().max_depth(10);
+    let result = taquin.solve_idastar_with_monitor(&ManhattanDistance, &mut limits);
+    assert!(matches!(result, Err(SolverError::LimitReached { depth: 11, .. })));
+
+    let mut limits = Limits::new().max_depth(20);
+    let result = taquin.solve_idastar_with_monitor(&ManhattanDistance, &mut limits);
+    assert_eq!(result.map(|moves| moves.len()), Ok(17));
+}
+
+#[test]
+fn limit_max_visited() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let mut limits = Limits::new().max_visited(1000);
+    let result = taquin.solve_astar_with_monitor(&ManhattanDistance, &mut limits);
+    assert_eq!(result.map(|moves| moves.len()), Ok(17));
+
+    let mut limits = Limits::new().max_visited(100);
+    let result = taquin.solve_with_monitor(&mut limits);
+    assert!(matches!(result, Err(SolverError::LimitReached { .. })));
+}
+
+#[test]
+fn limit_deadline() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let mut limits = Limits::new().deadline(Instant::now());
+    let result = taquin.solve_idastar_with_monitor(&ManhattanDistance, &mut limits);
+    assert!(matches!(result, Err(SolverError::LimitReached { expanded: 0, .. })));
+}
+
+#[test]
+fn cancelled() {
+    let taquin = Taquin::from_str(3, "5,0,3,  8,1,2,  4,7,6");
+    let token = CancellationToken::new();
+    let mut limits = Limits::new().cancellation(token.clone());
+    token.cancel();
+    assert_eq!(taquin.solve_astar_with_monitor(&ManhattanDistance, &mut limits), Err(SolverError::Cancelled));
+}
+
+#[test]
+fn packed_same_length_as_taquin() {
+    let taquin = Taquin::from_str(3, "6,4,7,  8,5,0,  3,2,1");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let expected = taquin.solve().expect("Oops!");
+
+    let result = packed.solve().expect("Oops!");
+    assert_eq!(result.len(), expected.len());
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved());
+
+    let result = packed.solve_astar(&ManhattanDistance).expect("Oops!");
+    assert_eq!(result.len(), expected.len());
+}
+
+#[test]
+fn packed_solve_a_4x4() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_idastar(&ManhattanDistance).expect("Oops!");
+    assert_eq!(result.len(), 40);
+}
+
+#[test]
+fn pattern_database_same_length_as_bfs() {
+    let database = PatternDatabase::build(3, &PatternDatabase::default_groups(3).unwrap()).unwrap();
+    let lines = ["5,0,3,  8,1,2,  4,7,6", "6,4,7,  8,5,0,  3,2,1", "1,2,3,  4,5,6,  7,0,8", "2,0,7,  1,8,4,  3,6,5"];
+    for line in lines.iter() {
+        let taquin = Taquin::from_str(3, line);
+        let bfs = taquin.solve().expect("Oops!");
+        let result = taquin.solve_astar(&database).expect("Oops!");
+        assert_eq!(result.len(), bfs.len(), "{}", line);
+    }
+}
+
+#[test]
+fn pattern_database_solve_a_4x4() {
+    let groups = vec![vec![1, 2, 3], vec![4, 7, 8], vec![5, 6, 9], vec![10, 13, 14], vec![11, 12, 15]];
+    let database = PatternDatabase::build(4, &groups).unwrap();
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_idastar(&database).expect("Oops!");
+    assert_eq!(result.len(), 40);
+}
+
+#[test]
+fn heuristics_are_admissible() {
+    let file = File::open("taquin_3x3.txt").expect("Oops!");
+    let lines = input::read_taquins(BufReader::new(file)).expect("Oops!");
+    let walking_distance = WalkingDistance::new(3);
+    for (line, taquin) in lines {
+        let taquin = taquin.expect("Oops!");
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        let length = packed.solve().expect("Oops!").len() as u32;
+
+        let manhattan = ManhattanDistance.estimate(&taquin);
+        let linear_conflict = LinearConflict.estimate(&taquin);
+        let walking = walking_distance.estimate(&taquin);
+        assert!(manhattan <= linear_conflict && linear_conflict <= length, "line {}", line);
+        assert!(manhattan <= walking && walking <= length, "line {}", line);
+    }
+}
+
+#[test]
+fn linear_conflict_solve_a_4x4() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_idastar(&LinearConflict).expect("Oops!");
+    assert_eq!(result.len(), 40);
+}
+
+#[test]
+fn walking_distance_solve_a_4x4() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_idastar(&WalkingDistance::new(4)).expect("Oops!");
+    assert_eq!(result.len(), 40);
+}
+
+#[test]
+fn packed_unsolvable() {
+    let taquin = Taquin::from_str(3, "2,1,3,  4,5,6,  7,8,0");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    assert_eq!(packed.solve(), Err(SolverError::Unsolvable));
+}
+
+#[test]
+fn bidirectional_already_solved() {
+    let taquin = Taquin::new(3);
+    assert_eq!(taquin.solve_bidirectional(), Ok(vec![]));
+}
+
+#[test]
+fn bidirectional_same_length_as_bfs() {
+    let lines = ["5,0,3,  8,1,2,  4,7,6", "6,4,7,  8,5,0,  3,2,1", "1,2,3,  4,5,6,  7,0,8", "2,0,7,  1,8,4,  3,6,5"];
+    for line in lines.iter() {
+        let taquin = Taquin::from_str(3, line);
+        let bfs = taquin.solve().expect("Oops!");
+        let result = taquin.solve_bidirectional().expect("Oops!");
+        assert_eq!(result.len(), bfs.len(), "{}", line);
+
+        let end = result.iter()
+            .fold(taquin, |t, m| t.move_hole(*m));
+        assert!(end.is_solved(), "{}", line);
+    }
+}
+
+#[test]
+fn bidirectional_packed_4x4() {
+    let taquin = Taquin::from_str(4, "2,3,4,8,  1,6,7,12,  5,10,11,0,  9,13,14,15");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_bidirectional().expect("Oops!");
+    assert_eq!(result.len(), 11);
+}
+
+#[test]
+fn bidirectional_unsolvable() {
+    let taquin = Taquin::from_str(3, "2,1,3,  4,5,6,  7,8,0");
+    assert_eq!(taquin.solve_bidirectional(), Err(SolverError::Unsolvable));
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel_same_length_as_bfs() {
+    let lines = ["5,0,3,  8,1,2,  4,7,6", "6,4,7,  8,5,0,  3,2,1", "1,2,3,  4,5,6,  7,0,8", "2,0,7,  1,8,4,  3,6,5"];
+    for line in lines.iter() {
+        let taquin = Taquin::from_str(3, line);
+        let bfs = taquin.solve().expect("Oops!");
+        let result = taquin.solve_parallel().expect("Oops!");
+        assert_eq!(result.len(), bfs.len(), "{}", line);
+
+        let end = result.iter()
+            .fold(taquin, |t, m| t.move_hole(*m));
+        assert!(end.is_solved(), "{}", line);
+    }
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel_unsolvable() {
+    let taquin = Taquin::from_str(3, "2,1,3,  4,5,6,  7,8,0");
+    assert_eq!(taquin.solve_parallel(), Err(SolverError::Unsolvable));
+}
+
+#[test]
+fn goal_spiral() {
+    let taquin = Taquin::from_str(3, "1,7,3,  6,0,4,  2,8,5").with_goal(Goal::spiral(3));
+    let bfs = taquin.solve().expect("Oops!");
+    assert_eq!(bfs.len(), 14);
+    assert_eq!(taquin.solve_astar(&LinearConflict).expect("Oops!").len(), 14);
+    assert_eq!(taquin.solve_bidirectional().expect("Oops!").len(), 14);
+
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_idastar(&WalkingDistance::for_goal(Goal::spiral(3))).expect("Oops!");
+    assert_eq!(result.len(), 14);
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved());
+    assert_eq!(end, Taquin::solved(Goal::spiral(3)));
+}
+
+#[test]
+fn goal_unsolvable() {
+    let taquin = Taquin::new(3).with_goal(Goal::spiral(3));
+    assert_eq!(taquin.solve_astar(&ManhattanDistance), Err(SolverError::Unsolvable));
+}
+
+#[test]
+fn rectangle_same_length_as_bfs() {
+    let dimensions = Dimensions::new(2, 4);
+    let taquin = Taquin::from_str(dimensions, "7,6,5,0,  4,3,2,1");
+    let bfs = taquin.solve().expect("Oops!");
+    assert_eq!(bfs.len(), 25);
+    assert_eq!(taquin.solve_astar(&LinearConflict).expect("Oops!").len(), 25);
+    assert_eq!(taquin.solve_idastar(&WalkingDistance::new(dimensions)).expect("Oops!").len(), 25);
+    assert_eq!(taquin.solve_bidirectional().expect("Oops!").len(), 25);
+
+    let database = PatternDatabase::build(dimensions, &PatternDatabase::default_groups(dimensions).unwrap()).unwrap();
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_astar(&database).expect("Oops!");
+    assert_eq!(result.len(), 25);
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved());
+}
+
+#[test]
+fn rectangle_solve_a_3x5() {
+    let dimensions = Dimensions::new(3, 5);
+    let taquin = Taquin::from_str(dimensions, "6,2,3,9,4,  1,12,7,0,5,  11,13,8,14,10");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    assert_eq!(packed.solve_idastar(&LinearConflict).expect("Oops!").len(), 26);
+    assert_eq!(packed.solve_idastar(&WalkingDistance::new(dimensions)).expect("Oops!").len(), 26);
+}
+
+#[test]
+fn rectangle_solve_a_4x6() {
+    let taquin = Taquin::from_str(Dimensions::new(4, 6), "8,13,3,4,5,6,  7,0,9,10,11,12,  19,1,15,16,17,18,  14,2,20,21,22,23");
+    let result = taquin.solve_idastar(&LinearConflict).expect("Oops!");
+    assert_eq!(result.len(), 24);
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved());
+}
+
+#[test]
+fn rectangle_unsolvable() {
+    let taquin = Taquin::from_str(Dimensions::new(3, 5), "11,6,2,3,4,  1,12,8,5,10,  0,13,7,9,14");
+    assert_eq!(taquin.solve_astar(&ManhattanDistance), Err(SolverError::Unsolvable));
+}
+
+#[test]
+fn solve_a_large_board() {
+    let moves = [Move::Down, Move::Down, Move::Right, Move::Up, Move::Right, Move::Down, Move::Left];
+    let taquin = moves.iter()
+        .fold(Taquin::new(20), |t, m| t.move_hole(*m));
+    let result = taquin.solve_idastar(&LinearConflict).expect("Oops!");
+    assert_eq!(result.len(), 7);
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved());
+}
+
+#[test]
+fn constructive_solve_a_3x3() {
+    let taquin = Taquin::from_str(3, "6,4,7,  8,5,0,  3,2,1");
+    let bfs = taquin.solve().expect("Oops!");
+    let result = taquin.solve_constructive().expect("Oops!");
+    let shorter = taquin.shorten(&result);
+    assert!(bfs.len() <= shorter.len() && shorter.len() <= result.len());
+
+    let end = shorter.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved());
+}
+
+#[test]
+fn constructive_solve_a_20x20() {
+    let values: Vec<String> = (0..400).map(|value| ((value * 7 + 3) % 400).to_string()).collect();
+    let taquin = Taquin::from_str(20, &values.join(","));
+    let result = taquin.solve_constructive().expect("Oops!");
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved());
+}
+
+#[test]
+fn weighted_astar_within_bound() {
+    let file = File::open("taquin_3x3.txt").expect("Oops!");
+    let lines = input::read_taquins(BufReader::new(file)).expect("Oops!");
+    for (line, taquin) in lines {
+        let taquin = taquin.expect("Oops!");
+        let packed = PackedTaquin::try_from(&taquin).unwrap();
+        let optimal = packed.solve().expect("Oops!").len();
+        assert_eq!(packed.solve_weighted_astar(&ManhattanDistance, 1.0).expect("Oops!").len(), optimal, "line {}", line);
+
+        let result = packed.solve_weighted_astar(&LinearConflict, 2.0).expect("Oops!");
+        assert!(optimal <= result.len() && result.len() <= 2 * optimal, "line {}", line);
+        let end = result.iter()
+            .fold(taquin, |t, m| t.move_hole(*m));
+        assert!(end.is_solved());
+    }
+}
+
+#[test]
+fn weighted_astar_solve_a_4x4() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let result = packed.solve_weighted_astar(&LinearConflict, 1.5).expect("Oops!");
+    // the optimal solution has 40 moves
+    assert!(40 <= result.len() && result.len() as f64 <= 1.5 * 40.0);
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved())
+}
+
+#[test]
+fn weighted_astar_huge_weight() {
+    let taquin = Taquin::from_str(3, "6,4,7,  8,5,0,  3,2,1");
+    let result = taquin.solve_weighted_astar(&ManhattanDistance, 1e300).expect("Oops!");
+
+    let end = result.iter()
+        .fold(taquin, |t, m| t.move_hole(*m));
+    assert!(end.is_solved())
+}
+
+#[test]
+fn weighted_astar_unsolvable() {
+    let taquin = Taquin::from_str(3, "2,1,3,  4,5,6,  7,8,0");
+    assert_eq!(taquin.solve_weighted_astar(&ManhattanDistance, 2.0), Err(SolverError::Unsolvable));
+}
+
+#[test]
+#[should_panic]
+fn weighted_astar_invalid_weight() {
+    let _ = Taquin::new(3).solve_weighted_astar(&ManhattanDistance, 0.5);
+}
+
+#[test]
+fn anytime_improves_to_optimal() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let packed = PackedTaquin::try_from(&taquin).unwrap();
+    let mut lengths = vec![];
+    let deadline = Instant::now() + std::time::Duration::from_secs(600);
+    let result = packed.solve_anytime(&LinearConflict, 3.0, deadline, |steps| lengths.push(steps.len()))
+        .expect("Oops!");
+    assert_eq!(result.len(), 40);
+    assert_eq!(lengths.last(), Some(&40));
+    assert!(lengths.windows(2).all(|pair| pair[1] < pair[0]), "{:?}", lengths);
+}
+
+#[test]
+fn anytime_deadline() {
+    let taquin = Taquin::from_str(4, "9,4,2,12,  3,0,14,6,  7,1,11,8,  5,13,10,15");
+    let result = taquin.solve_anytime(&LinearConflict, 2.0, Instant::now(), |_steps| {});
+    assert!(matches!(result, Err(SolverError::LimitReached { .. })));
+
+    // the first solution is kept when the limit is reached
+    let mut first = None;
+    let mut limits = Limits::new().max_expanded(5_000);
+    let result = taquin.solve_anytime_with_monitor(&LinearConflict, 3.0, &mut limits, |steps| {
+        first.get_or_insert(steps.len());
+    }).expect("Oops!");
+    assert!(first.is_some_and(|first| result.len() <= first));
+}